@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_word_entries_quote_lang;
+DROP INDEX IF EXISTS idx_word_entry_groups_source_id;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX idx_word_entry_groups_source_id ON word_entry_groups(source_id);
+CREATE INDEX idx_word_entries_quote_lang ON word_entries(quote_lang);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_word_entries_fulltext;
+DROP INDEX IF EXISTS idx_word_entries_quote_trgm;
+DROP INDEX IF EXISTS idx_word_entries_orth_trgm;
+DROP EXTENSION IF EXISTS pg_trgm;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+-- trigram indexes back the fuzzy search mode, as well as ILIKE patterns
+CREATE INDEX idx_word_entries_orth_trgm ON word_entries USING GIN (orth gin_trgm_ops);
+CREATE INDEX idx_word_entries_quote_trgm ON word_entries USING GIN (quote gin_trgm_ops);
+-- expression must match app::search::match_expression for the fulltext search mode
+CREATE INDEX idx_word_entries_fulltext ON word_entries
+    USING GIN (to_tsvector('simple', orth || ' ' || quote));
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_word_entry_readings_reading_trgm;
+DROP INDEX IF EXISTS idx_word_entry_readings_word_entry_id;
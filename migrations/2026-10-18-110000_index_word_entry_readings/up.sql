@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX idx_word_entry_readings_word_entry_id ON word_entry_readings(word_entry_id);
+CREATE INDEX idx_word_entry_readings_reading_trgm ON word_entry_readings USING GIN (reading gin_trgm_ops);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_word_entry_readings_search_key;
+ALTER TABLE word_entry_readings DROP COLUMN IF EXISTS search_key;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+ALTER TABLE word_entry_readings ADD COLUMN search_key VARCHAR;
+-- backfill cedict readings, equivalent to app::search::pinyin::search_key for tone-number readings
+UPDATE word_entry_readings SET search_key =
+    NULLIF(regexp_replace(replace(lower(word_entry_readings.reading), 'u:', 'v'), '[^a-z]', '', 'g'), '')
+    FROM word_entries
+    WHERE word_entries.id = word_entry_readings.word_entry_id AND word_entries.orth_lang = 'zho';
+CREATE INDEX idx_word_entry_readings_search_key ON word_entry_readings(search_key);
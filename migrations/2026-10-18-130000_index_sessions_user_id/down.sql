@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_sessions_last_accessed_at;
+DROP INDEX IF EXISTS idx_sessions_user_id;
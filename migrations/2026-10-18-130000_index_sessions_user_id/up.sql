@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX idx_sessions_user_id ON sessions(user_id);
+CREATE INDEX idx_sessions_last_accessed_at ON sessions(last_accessed_at);
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE password_resets (
+    token_hash VARCHAR(64) NOT NULL PRIMARY KEY, -- sha256 hex digest
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+CREATE INDEX idx_password_resets_user_id ON password_resets(user_id);
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN role VARCHAR NOT NULL DEFAULT 'reader'
+    CHECK (role IN ('reader', 'editor', 'admin'));
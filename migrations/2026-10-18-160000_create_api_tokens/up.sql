@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TABLE api_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    name VARCHAR NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE, -- sha256 hex digest
+    created_at TIMESTAMPTZ NOT NULL,
+    last_used_at TIMESTAMPTZ
+);
+CREATE INDEX idx_api_tokens_user_id ON api_tokens(user_id);
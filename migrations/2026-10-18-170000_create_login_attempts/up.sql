@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TABLE login_attempts (
+    id SERIAL PRIMARY KEY,
+    user_name VARCHAR NOT NULL,
+    client_ip TEXT,
+    succeeded BOOLEAN NOT NULL,
+    attempted_at TIMESTAMPTZ NOT NULL
+);
+CREATE INDEX idx_login_attempts_user_name ON login_attempts(user_name, attempted_at);
+CREATE INDEX idx_login_attempts_client_ip ON login_attempts(client_ip, attempted_at);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_word_entry_tags_tag;
+DROP INDEX IF EXISTS idx_word_entry_tags_word_entry_id;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX idx_word_entry_tags_word_entry_id ON word_entry_tags(word_entry_id);
+CREATE INDEX idx_word_entry_tags_tag ON word_entry_tags(tag, word_entry_id);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_word_entry_groups_upstream_key;
+ALTER TABLE word_entry_groups DROP COLUMN IF EXISTS upstream_hash;
+ALTER TABLE word_entry_groups DROP COLUMN IF EXISTS upstream_key;
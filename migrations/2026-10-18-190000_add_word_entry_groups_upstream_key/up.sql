@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE word_entry_groups ADD COLUMN upstream_key VARCHAR;
+ALTER TABLE word_entry_groups ADD COLUMN upstream_hash VARCHAR;
+CREATE UNIQUE INDEX idx_word_entry_groups_upstream_key ON word_entry_groups(source_id, upstream_key);
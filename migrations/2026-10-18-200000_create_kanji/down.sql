@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS kanji_meanings;
+DROP TABLE IF EXISTS kanji_readings;
+DROP TABLE IF EXISTS kanji;
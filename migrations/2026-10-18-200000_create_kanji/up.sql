@@ -0,0 +1,28 @@
+-- Your SQL goes here
+CREATE TABLE kanji (
+    id SERIAL PRIMARY KEY,
+    literal VARCHAR NOT NULL,
+    stroke_count INTEGER,
+    grade INTEGER,
+    jlpt INTEGER,
+    frequency INTEGER,
+    radical INTEGER,
+    nelson_radical INTEGER
+);
+CREATE UNIQUE INDEX idx_kanji_literal ON kanji(literal);
+
+CREATE TABLE kanji_readings (
+    id SERIAL PRIMARY KEY,
+    kanji_id INTEGER NOT NULL REFERENCES kanji(id) ON DELETE CASCADE,
+    reading VARCHAR NOT NULL,
+    reading_type VARCHAR NOT NULL
+);
+CREATE INDEX idx_kanji_readings_kanji_id ON kanji_readings(kanji_id);
+
+CREATE TABLE kanji_meanings (
+    id SERIAL PRIMARY KEY,
+    kanji_id INTEGER NOT NULL REFERENCES kanji(id) ON DELETE CASCADE,
+    meaning VARCHAR NOT NULL,
+    meaning_lang VARCHAR NOT NULL
+);
+CREATE INDEX idx_kanji_meanings_kanji_id ON kanji_meanings(kanji_id);
@@ -1,5 +1,19 @@
 /// length of secure session access tokens
 pub const TOKEN_LENGTH: usize = 64;
+/// length of generated request ids
+pub const REQUEST_ID_LENGTH: usize = 16;
+/// minimum length of user passwords
+pub const PASSWORD_MIN_LENGTH: usize = 8;
+/// maximum length of user passwords
+pub const PASSWORD_MAX_LENGTH: usize = 128;
+/// failed logins for an account before retries are delayed
+pub const LOGIN_ACCOUNT_FREE_ATTEMPTS: i64 = 3;
+/// failed logins for an account before it is locked out
+pub const LOGIN_ACCOUNT_LOCKOUT_ATTEMPTS: i64 = 10;
+/// failed logins from a client ip before retries are delayed
+pub const LOGIN_IP_FREE_ATTEMPTS: i64 = 10;
+/// failed logins from a client ip before it is locked out
+pub const LOGIN_IP_LOCKOUT_ATTEMPTS: i64 = 50;
 
 lazy_static::lazy_static! {
     // server bind address, default to `127.0.0.1:8301`
@@ -14,6 +28,37 @@ lazy_static::lazy_static! {
     pub static ref DATABASE_URI: String = std::env::var("DATABASE_URL")
         .expect("\n\n  DATABASE_URI environment variable required for postgres connection\n\n");
 
+    // sessions expire after this many idle minutes, default to 120
+    pub static ref SESSION_IDLE_TIMEOUT: chrono::Duration = chrono::Duration::minutes(
+        std::env::var("SESSION_IDLE_TIMEOUT_MINUTES").ok()
+            .and_then(|minutes| minutes.parse().ok())
+            .unwrap_or(120));
+
+    // sessions expire this many hours after login regardless of use, default to 24
+    pub static ref SESSION_MAX_AGE: chrono::Duration = chrono::Duration::hours(
+        std::env::var("SESSION_MAX_AGE_HOURS").ok()
+            .and_then(|hours| hours.parse().ok())
+            .unwrap_or(24));
+
+    // failed logins are counted over this many minutes, which is also the lockout length, default to 15
+    pub static ref LOGIN_ATTEMPT_WINDOW: chrono::Duration = chrono::Duration::minutes(
+        std::env::var("LOGIN_ATTEMPT_WINDOW_MINUTES").ok()
+            .and_then(|minutes| minutes.parse().ok())
+            .unwrap_or(15));
+
+    // proxies trusted to set X-Forwarded-For, comma separated ip addresses, default to none
+    pub static ref TRUSTED_PROXIES: Vec<std::net::IpAddr> = std::env::var("TRUSTED_PROXIES")
+        .map(|proxies| proxies.split(',')
+            .filter_map(|proxy| proxy.trim().parse().ok())
+            .collect())
+        .unwrap_or_default();
+
+    // password reset tokens expire after this many hours, default to 24
+    pub static ref PASSWORD_RESET_EXPIRY: chrono::Duration = chrono::Duration::hours(
+        std::env::var("PASSWORD_RESET_EXPIRY_HOURS").ok()
+            .and_then(|hours| hours.parse().ok())
+            .unwrap_or(24));
+
     /// loads SECRET_KEY environment variable for use in password hash validation
     pub static ref SECRET_KEY: String = std::env::var("SECRET_KEY")
         .expect("\n\n  Cowardly refusing to run without SECRET_KEY=(32-character string) environment variable\n\n");
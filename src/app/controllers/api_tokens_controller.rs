@@ -0,0 +1,135 @@
+use actix_web::{error::BlockingError, web, HttpResponse};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde::{Deserialize,Serialize};
+
+use crate::app::database::DbPool;
+use crate::app::errors::ServiceError;
+use crate::app::middleware::auth_middleware::{CookieSessionUser, SessionUser};
+use crate::app::models::{ApiToken, NewApiToken};
+use crate::app::security::{hash_token, random_token};
+use crate::app::validation::{Validate, Validator};
+
+/// maximum length of api token names
+const NAME_MAX_LENGTH: usize = 64;
+
+/// struct for storing api token creation request data
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateApiTokenData {
+    pub name: String,
+}
+
+impl Validate for CreateApiTokenData {
+    fn validate(&self) -> Result<(), ServiceError> {
+        let mut validator = Validator::new();
+        validator.text("name", &self.name, NAME_MAX_LENGTH);
+        validator.finish()
+    }
+}
+
+/// POST /api_tokens result, the only time the token itself is returned
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateApiTokenResult {
+    pub api_token: ApiToken,
+    pub token: String,
+}
+
+/// GET /api_tokens
+pub async fn list_api_tokens(
+    session_user: SessionUser,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let res = web::block(move || {
+        use crate::schema::api_tokens::dsl::{api_tokens, id, user_id};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        let user_api_tokens = api_tokens
+            .filter(user_id.eq(session_user.id))
+            .order(id)
+            .get_results::<ApiToken>(conn)?;
+
+        Ok(user_api_tokens)
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Ok().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// POST /api_tokens, requires a cookie session so api tokens cannot mint more tokens
+pub async fn create_api_token(
+    session_user: CookieSessionUser,
+    token_data: web::Json<CreateApiTokenData>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    token_data.validate()?;
+    let token_name = token_data.into_inner().name.trim().to_string();
+
+    let res = web::block(move || {
+        use crate::schema::api_tokens::dsl::{api_tokens};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        let token = random_token()?;
+        let new_api_token = NewApiToken {
+            user_id: session_user.id,
+            name: token_name,
+            token_hash: hash_token(&token),
+            created_at: Utc::now(),
+        };
+        let api_token: ApiToken = diesel::insert_into(api_tokens)
+            .values(&new_api_token)
+            .get_result(conn)?;
+
+        Ok(CreateApiTokenResult { api_token, token })
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Created().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// DELETE /api_tokens/{id}
+pub async fn delete_api_token(
+    session_user: SessionUser,
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let api_token_id = path.into_inner();
+
+    let res = web::block(move || {
+        use crate::schema::api_tokens::dsl::{api_tokens, id, user_id};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        // only tokens of the current user can be revoked
+        let deleted_count = diesel::delete(
+            api_tokens
+            .filter(id.eq(api_token_id))
+            .filter(user_id.eq(session_user.id))
+        ).execute(conn)?;
+
+        if deleted_count < 1 {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(())
+    }).await;
+
+    match res {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
@@ -1,14 +1,17 @@
 use actix_identity::Identity;
-use actix_web::{error::BlockingError, web, HttpResponse};
+use actix_web::{error::BlockingError, web, HttpRequest, HttpResponse};
+use chrono::{DateTime,Duration,Utc};
 use diesel::prelude::*;
 use diesel::PgConnection;
 use serde::{Deserialize,Serialize};
 
+use crate::app::config;
 use crate::app::database::DbPool;
 use crate::app::errors::ServiceError;
-use crate::app::middleware::auth_middleware::SessionUser;
-use crate::app::models::{Session, User};
-use crate::app::security::verify_password;
+use crate::app::middleware::auth_middleware::{client_ip, purge_expired_sessions, CookieSessionUser, SessionUser};
+use crate::app::models::{NewLoginAttempt, PasswordReset, Session, User};
+use crate::app::security::{hash_password, hash_token, validate_password_strength, verify_password};
+use crate::app::validation::{Validate, Validator};
 
 /// struct for storing login request data
 #[derive(Debug, Deserialize, Serialize)]
@@ -17,6 +20,182 @@ pub struct AuthRequestData {
     pub password: String,
 }
 
+/// struct for storing password change request data
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChangePasswordRequestData {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// struct for storing password reset request data
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ResetPasswordRequestData {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// checks a password to be verified is given, and short enough to be worth hashing
+fn validate_password_given(validator: &mut Validator, field: &str, password: &str) {
+    validator.check(!password.is_empty(), field, "must not be empty");
+    validator.check(password.chars().count() <= config::PASSWORD_MAX_LENGTH, field,
+        format!("must be at most {} characters", config::PASSWORD_MAX_LENGTH));
+}
+
+impl Validate for AuthRequestData {
+    fn validate(&self) -> Result<(), ServiceError> {
+        let mut validator = Validator::new();
+        validator.check(!self.name.is_empty(), "name", "must not be empty");
+        validate_password_given(&mut validator, "password", &self.password);
+        validator.finish()
+    }
+}
+
+impl Validate for ChangePasswordRequestData {
+    fn validate(&self) -> Result<(), ServiceError> {
+        let mut validator = Validator::new();
+        validate_password_given(&mut validator, "current_password", &self.current_password);
+        validator.merge(validate_password_strength("new_password", &self.new_password))?;
+        validator.finish()
+    }
+}
+
+impl Validate for ResetPasswordRequestData {
+    fn validate(&self) -> Result<(), ServiceError> {
+        let mut validator = Validator::new();
+        validator.check(!self.token.is_empty(), "token", "must not be empty");
+        validator.merge(validate_password_strength("new_password", &self.new_password))?;
+        validator.finish()
+    }
+}
+
+/// seconds to wait before another login after recent failures, newest first, if any
+fn login_retry_after(
+    recent_failures: &[DateTime<Utc>],
+    free_attempts: i64,
+    lockout_attempts: i64,
+) -> Option<i64> {
+    let failures = recent_failures.len() as i64;
+    let last_failure = *recent_failures.first()?;
+    if failures < free_attempts {
+        return None;
+    }
+
+    let delay = if failures >= lockout_attempts {
+        *config::LOGIN_ATTEMPT_WINDOW
+    } else {
+        // doubles with each failure past the free attempts, up to the lockout length
+        let backoff = Duration::seconds(1 << (failures - free_attempts).min(20));
+        backoff.min(*config::LOGIN_ATTEMPT_WINDOW)
+    };
+
+    // round up so clients never retry too early
+    let remaining_ms = (last_failure + delay - Utc::now()).num_milliseconds();
+    if remaining_ms > 0 { Some((remaining_ms + 999) / 1000) } else { None }
+}
+
+/// advisory lock namespaces of login attempts, keeping account and ip locks apart
+const LOGIN_ACCOUNT_LOCK: i32 = 1;
+const LOGIN_IP_LOCK: i32 = 2;
+
+/// locks login attempts for an account and client ip until the end of the transaction,
+/// so concurrent attempts are checked against each other's recorded failures
+fn lock_login_attempts(
+    conn: &PgConnection,
+    login_name: &str,
+    login_ip: Option<&str>,
+) -> QueryResult<()> {
+    use diesel::sql_types::{Integer, Text};
+
+    // always lock the account before the ip, so concurrent logins cannot deadlock
+    let mut lock_keys = vec![(LOGIN_ACCOUNT_LOCK, login_name)];
+    lock_keys.extend(login_ip.map(|login_ip| (LOGIN_IP_LOCK, login_ip)));
+    for (lock_namespace, lock_key) in lock_keys {
+        diesel::sql_query("SELECT pg_advisory_xact_lock($1, hashtext($2))")
+            .bind::<Integer, _>(lock_namespace)
+            .bind::<Text, _>(lock_key)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// rejects logins for an account or client ip with too many recent failures
+fn check_login_throttle(
+    conn: &PgConnection,
+    login_name: &str,
+    login_ip: Option<&str>,
+) -> Result<(), ServiceError> {
+    use crate::schema::login_attempts::dsl::{attempted_at, client_ip, login_attempts,
+        succeeded, user_name};
+
+    let window_start = Utc::now() - *config::LOGIN_ATTEMPT_WINDOW;
+
+    // account failures only count since its last successful login
+    let last_success: Option<DateTime<Utc>> = login_attempts
+        .filter(user_name.eq(login_name))
+        .filter(succeeded.eq(true))
+        .select(diesel::dsl::max(attempted_at))
+        .first(conn)?;
+    let account_since = last_success.map_or(window_start, |success| success.max(window_start));
+
+    // no need to count past the lockout threshold
+    let account_failures = login_attempts
+        .filter(user_name.eq(login_name))
+        .filter(succeeded.eq(false))
+        .filter(attempted_at.gt(account_since))
+        .select(attempted_at)
+        .order(attempted_at.desc())
+        .limit(config::LOGIN_ACCOUNT_LOCKOUT_ATTEMPTS)
+        .load::<DateTime<Utc>>(conn)?;
+    let mut retry_after = login_retry_after(&account_failures,
+        config::LOGIN_ACCOUNT_FREE_ATTEMPTS, config::LOGIN_ACCOUNT_LOCKOUT_ATTEMPTS);
+
+    if let Some(login_ip) = login_ip {
+        let ip_failures = login_attempts
+            .filter(client_ip.eq(login_ip))
+            .filter(succeeded.eq(false))
+            .filter(attempted_at.gt(window_start))
+            .select(attempted_at)
+            .order(attempted_at.desc())
+            .limit(config::LOGIN_IP_LOCKOUT_ATTEMPTS)
+            .load::<DateTime<Utc>>(conn)?;
+        retry_after = retry_after.max(login_retry_after(&ip_failures,
+            config::LOGIN_IP_FREE_ATTEMPTS, config::LOGIN_IP_LOCKOUT_ATTEMPTS));
+    }
+
+    match retry_after {
+        Some(seconds) => Err(ServiceError::TooManyRequests(seconds)),
+        None => Ok(()),
+    }
+}
+
+/// records a login attempt for throttling and review by admins
+fn record_login_attempt(
+    conn: &PgConnection,
+    login_name: &str,
+    login_ip: Option<String>,
+    login_succeeded: bool,
+) -> QueryResult<usize> {
+    use crate::schema::login_attempts::dsl::{login_attempts};
+
+    diesel::insert_into(login_attempts)
+        .values(&NewLoginAttempt {
+            user_name: login_name.to_string(),
+            client_ip: login_ip,
+            succeeded: login_succeeded,
+            attempted_at: Utc::now(),
+        })
+        .execute(conn)
+}
+
+/// deletes login attempts too old to count towards throttling, returning the number of attempts removed
+fn purge_old_login_attempts(conn: &PgConnection) -> QueryResult<usize> {
+    use crate::schema::login_attempts::dsl::{attempted_at, login_attempts};
+
+    diesel::delete(login_attempts.filter(
+        attempted_at.lt(Utc::now() - *config::LOGIN_ATTEMPT_WINDOW)
+    )).execute(conn)
+}
+
 /// GET /auth
 pub async fn get_me(session_user: SessionUser) -> HttpResponse {
     HttpResponse::Ok().json(session_user)
@@ -24,11 +203,14 @@ pub async fn get_me(session_user: SessionUser) -> HttpResponse {
 
 /// POST /auth
 pub async fn login(
+    req: HttpRequest,
     auth_data: web::Json<AuthRequestData>,
     id: Identity,
     pool: web::Data<DbPool>,
 ) -> Result<HttpResponse, ServiceError> {
     let auth_data_inner: AuthRequestData = auth_data.into_inner();
+    auth_data_inner.validate()?;
+    let login_ip = client_ip(&req);
 
     let res = web::block(move || {
         use crate::schema::sessions::dsl::{sessions};
@@ -36,22 +218,38 @@ pub async fn login(
 
         let conn: &PgConnection = &pool.get().unwrap();
 
-        let mut items = users
-            .filter(name.eq(&auth_data_inner.name))
-            .load::<User>(conn)?;
-
-        if let Some(user) = items.pop() {
-            if let Ok(matching) = verify_password(&user.passhash, &auth_data_inner.password) {
-                if matching {
-                    let new_session = Session::create(user.id);
-                    let created_session: Session =
-                        diesel::insert_into(sessions).values(&new_session).get_result(conn)?;
-                    return Ok(created_session.token);
+        // the failed attempt must be committed, so the transaction results in None for bad credentials
+        let token = conn.transaction::<_, ServiceError, _>(|| {
+            lock_login_attempts(conn, &auth_data_inner.name, login_ip.as_deref())?;
+
+            // refuse throttled logins before spending time on password verification
+            check_login_throttle(conn, &auth_data_inner.name, login_ip.as_deref())?;
+
+            let mut items = users
+                .filter(name.eq(&auth_data_inner.name))
+                .load::<User>(conn)?;
+
+            if let Some(user) = items.pop() {
+                if let Ok(matching) = verify_password(&user.passhash, &auth_data_inner.password) {
+                    if matching {
+                        record_login_attempt(conn, &auth_data_inner.name, login_ip, true)?;
+
+                        // clean up stale sessions and login attempts from all users while logging in
+                        purge_expired_sessions(conn)?;
+                        purge_old_login_attempts(conn)?;
+
+                        let new_session = Session::create(user.id);
+                        let created_session: Session =
+                            diesel::insert_into(sessions).values(&new_session).get_result(conn)?;
+                        return Ok(Some(created_session.token));
+                    }
                 }
             }
-        }
 
-        Err(ServiceError::Unauthorized)
+            record_login_attempt(conn, &auth_data_inner.name, login_ip, false)?;
+            Ok(None)
+        })?;
+        token.ok_or(ServiceError::Unauthorized)
     }).await;
 
     match res {
@@ -85,3 +283,123 @@ pub async fn logout(
     id.forget();
     HttpResponse::Ok().finish()
 }
+
+/// PUT /auth/password
+pub async fn change_password(
+    req: HttpRequest,
+    session_user: CookieSessionUser,
+    password_data: web::Json<ChangePasswordRequestData>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let password_data_inner: ChangePasswordRequestData = password_data.into_inner();
+    password_data_inner.validate()?;
+    let login_ip = client_ip(&req);
+
+    let res = web::block(move || {
+        use crate::schema::api_tokens;
+        use crate::schema::sessions::dsl::{sessions, token, user_id};
+        use crate::schema::users::dsl::{passhash, users};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+        let new_passhash = hash_password(&password_data_inner.new_password)?;
+
+        // the failed attempt must be committed, so the transaction results in false for a wrong password
+        let changed = conn.transaction::<_, ServiceError, _>(|| {
+            // checking the current password counts as a login attempt, so it is throttled the same way
+            lock_login_attempts(conn, &session_user.name, login_ip.as_deref())?;
+            check_login_throttle(conn, &session_user.name, login_ip.as_deref())?;
+
+            let user = users.find(session_user.id).first::<User>(conn)?;
+            if !verify_password(&user.passhash, &password_data_inner.current_password)? {
+                record_login_attempt(conn, &user.name, login_ip, false)?;
+                return Ok(false);
+            }
+            record_login_attempt(conn, &user.name, login_ip, true)?;
+
+            diesel::update(users.find(user.id))
+                .set(passhash.eq(&new_passhash))
+                .execute(conn)?;
+
+            // sign out every other session of this user, keeping the current one
+            diesel::delete(
+                sessions
+                .filter(user_id.eq(user.id))
+                .filter(token.ne(&session_user.session_token))
+            ).execute(conn)?;
+
+            // revoke all api tokens of this user, they may have leaked with the old password
+            diesel::delete(
+                api_tokens::table
+                .filter(api_tokens::user_id.eq(user.id))
+            ).execute(conn)?;
+            Ok(true)
+        })?;
+        if changed { Ok(()) } else { Err(ServiceError::Unauthorized) }
+    }).await;
+
+    match res {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// POST /auth/password_reset
+pub async fn reset_password(
+    reset_data: web::Json<ResetPasswordRequestData>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let reset_data_inner: ResetPasswordRequestData = reset_data.into_inner();
+    reset_data_inner.validate()?;
+
+    let res = web::block(move || {
+        use crate::schema::{api_tokens, password_resets, sessions};
+        use crate::schema::users::dsl::{passhash, users};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        conn.transaction::<_, ServiceError, _>(|| {
+            let reset = password_resets::table
+                .find(hash_token(&reset_data_inner.token))
+                .for_update()
+                .first::<PasswordReset>(conn)
+                .optional()?
+                .filter(|reset| !reset.is_expired())
+                .ok_or_else(|| ServiceError::BadRequest("invalid or expired reset token".to_string()))?;
+
+            let new_passhash = hash_password(&reset_data_inner.new_password)?;
+            diesel::update(users.find(reset.user_id))
+                .set(passhash.eq(&new_passhash))
+                .execute(conn)?;
+
+            // reset tokens are single use, drop any others issued for this user as well
+            diesel::delete(
+                password_resets::table
+                .filter(password_resets::user_id.eq(reset.user_id))
+            ).execute(conn)?;
+
+            // sign out all sessions of this user
+            diesel::delete(
+                sessions::table
+                .filter(sessions::user_id.eq(reset.user_id))
+            ).execute(conn)?;
+
+            // and revoke all api tokens of this user
+            diesel::delete(
+                api_tokens::table
+                .filter(api_tokens::user_id.eq(reset.user_id))
+            ).execute(conn)?;
+            Ok(())
+        })
+    }).await;
+
+    match res {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
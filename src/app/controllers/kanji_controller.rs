@@ -0,0 +1,100 @@
+use actix_web::{error::BlockingError, web, HttpResponse};
+use diesel::prelude::*;
+use itertools::multizip;
+use serde::{Deserialize,Serialize};
+
+use crate::app::controllers::word_entries_controller::ListWordEntriesResultRecord;
+use crate::app::database::DbPool;
+use crate::app::errors::ServiceError;
+use crate::app::models::{Kanji,KanjiMeaning,KanjiReading};
+
+/// GET /kanji/{char} result, a character with its readings and meanings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KanjiRecord {
+    pub kanji: Kanji,
+    /// on, kun and nanori readings, in dictionary order
+    pub kanji_readings: Vec<KanjiReading>,
+    pub kanji_meanings: Vec<KanjiMeaning>,
+}
+
+/// loads the kanji records of the characters that are in the dictionary
+pub(crate) fn load_kanji_records(
+    conn: &PgConnection,
+    literals: &[String],
+) -> Result<Vec<KanjiRecord>, ServiceError> {
+    use crate::schema::{kanji,kanji_meanings,kanji_readings};
+
+    let kanji_items = kanji::table
+        .filter(kanji::literal.eq_any(literals))
+        .get_results::<Kanji>(conn)?;
+    let kanji_readings_items = KanjiReading::belonging_to(&kanji_items)
+        .order(kanji_readings::id)
+        .get_results::<KanjiReading>(conn)?
+        .grouped_by(&kanji_items);
+    let kanji_meanings_items = KanjiMeaning::belonging_to(&kanji_items)
+        .order(kanji_meanings::id)
+        .get_results::<KanjiMeaning>(conn)?
+        .grouped_by(&kanji_items);
+
+    // zip items
+    Ok(multizip((kanji_items, kanji_readings_items, kanji_meanings_items)).map({|t|
+        KanjiRecord {
+            kanji: t.0,
+            kanji_readings: t.1,
+            kanji_meanings: t.2,
+        }
+    }).collect::<Vec<_>>())
+}
+
+/// adds the kanji in each record's orth, in order of appearance
+pub(crate) fn expand_kanji(
+    conn: &PgConnection,
+    records: &mut [ListWordEntriesResultRecord],
+) -> Result<(), ServiceError> {
+    let mut literals = records.iter()
+        .flat_map(|record| record.word_entry.orth.chars().map(|c| c.to_string()))
+        .collect::<Vec<_>>();
+    literals.sort();
+    literals.dedup();
+    let kanji_records = load_kanji_records(conn, &literals)?;
+
+    for record in records.iter_mut() {
+        let mut orth_kanji = Vec::<KanjiRecord>::new();
+        for c in record.word_entry.orth.chars() {
+            let literal = c.to_string();
+            if orth_kanji.iter().any(|k| k.kanji.literal == literal) {
+                continue;
+            }
+            if let Some(kanji_record) = kanji_records.iter().find(|k| k.kanji.literal == literal) {
+                orth_kanji.push(kanji_record.clone());
+            }
+        }
+        record.kanji = Some(orth_kanji);
+    }
+    Ok(())
+}
+
+/// GET /kanji/{char}
+pub async fn get_kanji(
+    literal: web::Path<String>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let literal = literal.into_inner();
+    if literal.chars().count() != 1 {
+        return Err(ServiceError::invalid_field("char", "must be a single character"));
+    }
+
+    let res = web::block(move || {
+        let conn: &PgConnection = &pool.get().unwrap();
+        load_kanji_records(conn, &[literal])?.pop()
+            .ok_or(ServiceError::NotFound)
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Ok().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
@@ -0,0 +1,59 @@
+use actix_web::{error::BlockingError, web, HttpResponse};
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde::{Deserialize,Serialize};
+
+use crate::app::database::DbPool;
+use crate::app::errors::ServiceError;
+use crate::app::middleware::role_middleware::AdminUser;
+use crate::app::models::LoginAttempt;
+
+/// maximum number of attempts listed
+const LIST_LIMIT: i64 = 100;
+
+/// GET /login_attempts query params
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListLoginAttemptsParams {
+    pub user_name: Option<String>,
+    pub client_ip: Option<String>,
+}
+
+/// GET /login_attempts
+pub async fn list_failed_login_attempts(
+    _admin_user: AdminUser,
+    params: web::Query<ListLoginAttemptsParams>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let params_inner: ListLoginAttemptsParams = params.into_inner();
+
+    let res = web::block(move || {
+        use crate::schema::login_attempts::dsl::{attempted_at, client_ip, id, login_attempts,
+            succeeded, user_name};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        // most recent failures first
+        let mut query = login_attempts
+            .filter(succeeded.eq(false))
+            .order((attempted_at.desc(), id.desc()))
+            .limit(LIST_LIMIT)
+            .into_boxed();
+        if let Some(ref filter_user_name) = params_inner.user_name {
+            query = query.filter(user_name.eq(filter_user_name));
+        }
+        if let Some(ref filter_client_ip) = params_inner.client_ip {
+            query = query.filter(client_ip.eq(filter_client_ip));
+        }
+
+        let failed_attempts = query.load::<LoginAttempt>(conn)?;
+        Ok(failed_attempts)
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Ok().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
@@ -1,2 +1,9 @@
+pub mod api_tokens_controller;
 pub mod auth_controller;
+pub mod kanji_controller;
+pub mod login_attempts_controller;
+pub mod sessions_controller;
+pub mod tags_controller;
+pub mod users_controller;
 pub mod word_entries_controller;
+pub mod word_entry_groups_controller;
@@ -0,0 +1,106 @@
+use actix_identity::Identity;
+use actix_web::{error::BlockingError, web, HttpResponse};
+use chrono::{DateTime,Utc};
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde::{Deserialize,Serialize};
+
+use crate::app::database::DbPool;
+use crate::app::errors::ServiceError;
+use crate::app::middleware::auth_middleware::SessionUser;
+use crate::app::models::Session;
+use crate::app::security::hash_token;
+
+/// GET /sessions list result record
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SessionRecord {
+    /// hash of the session token, identifying the session without revealing the cookie
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub last_accessed_at: DateTime<Utc>,
+    pub accessed_by_client_ip: Option<String>,
+    /// true for the session making the request
+    pub current: bool,
+}
+
+/// GET /sessions
+pub async fn list_sessions(
+    session_user: SessionUser,
+    id: Identity,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let current_token = id.identity();
+
+    let res = web::block(move || {
+        use crate::schema::sessions::dsl::{last_accessed_at, sessions, user_id};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        let user_sessions = sessions
+            .filter(user_id.eq(session_user.id))
+            .order(last_accessed_at.desc())
+            .get_results::<Session>(conn)?;
+
+        // expired sessions are not listed, they are purged on access or login
+        Ok(user_sessions.into_iter()
+            .filter(|session| !session.is_expired())
+            .map(|session| SessionRecord {
+                current: current_token.as_ref() == Some(&session.token),
+                id: hash_token(&session.token),
+                created_at: session.created_at,
+                last_accessed_at: session.last_accessed_at,
+                accessed_by_client_ip: session.accessed_by_client_ip,
+            })
+            .collect::<Vec<_>>())
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Ok().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// DELETE /sessions/{id}
+pub async fn delete_session(
+    session_user: SessionUser,
+    session_id: web::Path<String>,
+    id: Identity,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let session_id = session_id.into_inner();
+    let is_current = id.identity().map(|current_token| hash_token(&current_token)).as_ref() == Some(&session_id);
+
+    let res = web::block(move || {
+        use crate::schema::sessions::dsl::{sessions, token, user_id};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        // only sessions belonging to the user can be revoked
+        let session_token = sessions
+            .select(token)
+            .filter(user_id.eq(session_user.id))
+            .get_results::<String>(conn)?
+            .into_iter()
+            .find(|session_token| hash_token(session_token) == session_id)
+            .ok_or(ServiceError::NotFound)?;
+
+        diesel::delete(sessions.find(&session_token)).execute(conn)?;
+        Ok(())
+    }).await;
+
+    match res {
+        Ok(_) => {
+            if is_current {
+                id.forget();
+            }
+            Ok(HttpResponse::Ok().finish())
+        }
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
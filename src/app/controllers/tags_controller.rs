@@ -0,0 +1,20 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+use crate::app::tags::{TagCategory, TagSource, TAGS};
+
+/// GET /tags list params
+#[derive(Debug, Deserialize)]
+pub struct ListTagsParams {
+    pub category: Option<TagCategory>,
+    pub source: Option<TagSource>,
+}
+
+/// GET /tags
+pub async fn list_tags(params: web::Query<ListTagsParams>) -> HttpResponse {
+    let tags = TAGS.iter()
+        .filter(|info| params.category.is_none() || params.category == Some(info.category))
+        .filter(|info| params.source.is_none() || params.source == Some(info.source))
+        .collect::<Vec<_>>();
+    HttpResponse::Ok().json(tags)
+}
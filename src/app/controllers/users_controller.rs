@@ -0,0 +1,175 @@
+use actix_web::{error::BlockingError, web, HttpResponse};
+use diesel::prelude::*;
+use diesel::PgConnection;
+use diesel::result::{DatabaseErrorKind, Error as DBError};
+use serde::{Deserialize,Serialize};
+
+use crate::app::database::DbPool;
+use crate::app::errors::ServiceError;
+use crate::app::middleware::role_middleware::AdminUser;
+use crate::app::models::{NewUser, PasswordReset, Role, SlimUser, User};
+use crate::app::security::{hash_password, random_token, validate_password_strength};
+use crate::app::validation::{Validate, Validator};
+
+/// minimum length of user names
+const NAME_MIN_LENGTH: usize = 3;
+/// maximum length of user names
+const NAME_MAX_LENGTH: usize = 32;
+
+/// struct for storing registration request data
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateUserRequestData {
+    pub name: String,
+    pub password: String,
+}
+
+/// struct for storing role change request data
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateUserRoleData {
+    pub role: Role,
+}
+
+/// POST /users/{id}/password_reset result, the only time the reset token itself is returned
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatePasswordResetResult {
+    pub password_reset: PasswordReset,
+    pub token: String,
+}
+
+/// checks a user name is of valid length and only contains letters, digits, _ or -
+pub fn validate_user_name(name: &str) -> Result<(), ServiceError> {
+    let name_length = name.chars().count();
+    if !(NAME_MIN_LENGTH..=NAME_MAX_LENGTH).contains(&name_length) {
+        return Err(ServiceError::invalid_field("name", format!(
+            "must be between {} and {} characters", NAME_MIN_LENGTH, NAME_MAX_LENGTH)));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(ServiceError::invalid_field("name",
+            "may only contain letters, digits, _ or -"));
+    }
+    Ok(())
+}
+
+impl Validate for CreateUserRequestData {
+    fn validate(&self) -> Result<(), ServiceError> {
+        let mut validator = Validator::new();
+        validator.merge(validate_user_name(&self.name))?;
+        validator.merge(validate_password_strength("password", &self.password))?;
+        validator.finish()
+    }
+}
+
+/// POST /users
+pub async fn create_user(
+    user_data: web::Json<CreateUserRequestData>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let user_data_inner: CreateUserRequestData = user_data.into_inner();
+
+    // validate before spending time on hashing
+    user_data_inner.validate()?;
+
+    let res = web::block(move || {
+        use crate::schema::users::dsl::{users};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        let passhash = hash_password(&user_data_inner.password)?;
+        let new_user = NewUser::from_details(user_data_inner.name, passhash);
+
+        // users.name is UNIQUE, report taken names against the name field
+        let created_user: User = diesel::insert_into(users)
+            .values(&new_user)
+            .get_result(conn)
+            .map_err(|err| match err {
+                DBError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) =>
+                    ServiceError::invalid_field("name", "is already taken"),
+                _ => ServiceError::from(err),
+            })?;
+
+        Ok(SlimUser::from(created_user))
+    }).await;
+
+    match res {
+        Ok(slim_user) => Ok(HttpResponse::Created().json(slim_user)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// PUT /users/{id}/role
+pub async fn update_user_role(
+    admin_user: AdminUser,
+    path: web::Path<i32>,
+    role_data: web::Json<UpdateUserRoleData>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let user_id = path.into_inner();
+    let new_role = role_data.into_inner().role;
+
+    // keep admins from locking themselves out
+    if user_id == admin_user.id {
+        return Err(ServiceError::BadRequest("cannot change your own role".to_string()));
+    }
+
+    let res = web::block(move || {
+        use crate::schema::users::dsl::{role, users};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        let updated_user: User = diesel::update(users.find(user_id))
+            .set(role.eq(new_role.as_str()))
+            .get_result(conn)
+            .optional()?
+            .ok_or(ServiceError::NotFound)?;
+
+        Ok(SlimUser::from(updated_user))
+    }).await;
+
+    match res {
+        Ok(slim_user) => Ok(HttpResponse::Ok().json(slim_user)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// POST /users/{id}/password_reset
+pub async fn create_password_reset(
+    _admin_user: AdminUser,
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let user_id = path.into_inner();
+
+    let res = web::block(move || {
+        use crate::schema::password_resets;
+        use crate::schema::users::dsl::{users};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        users.find(user_id)
+            .first::<User>(conn)
+            .optional()?
+            .ok_or(ServiceError::NotFound)?;
+
+        let token = random_token()?;
+        let new_reset = PasswordReset::create(user_id, &token);
+        let password_reset: PasswordReset = diesel::insert_into(password_resets::table)
+            .values(&new_reset)
+            .get_result(conn)?;
+
+        Ok(CreatePasswordResetResult { password_reset, token })
+    }).await;
+
+    match res {
+        Ok(reset) => Ok(HttpResponse::Created().json(reset)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
@@ -1,25 +1,160 @@
 use actix_web::{error::BlockingError, web, HttpResponse};
+use diesel::pg::Pg;
 use diesel::prelude::*;
 use itertools::multizip;
 use serde::{Deserialize,Serialize};
 
+use crate::app::controllers::kanji_controller::{expand_kanji, KanjiRecord};
 use crate::app::database::DbPool;
 use crate::app::errors::ServiceError;
-use crate::app::models::{WordEntry,WordEntryNote,WordEntryReading,WordEntryTag};
+use crate::app::middleware::auth_middleware::SessionUser;
+use crate::app::middleware::role_middleware::EditorUser;
+use crate::app::search::{match_expression, matched_readings_expression, reading_search_key,
+    score_expression, MatchMode, SearchField, SearchMode, WordEntriesPredicate};
+use crate::app::models::{NewWordEntry,NewWordEntryNote,NewWordEntryReading,NewWordEntryTag,
+    WordEntry,WordEntryChangeset,WordEntryGroup,WordEntryNote,WordEntryReading,WordEntryTag};
+use crate::app::validation::{Validate, Validator};
 
-const PER_PAGE: i64 = 30;
+/// page size when per_page is not given
+const DEFAULT_PER_PAGE: i64 = 30;
+/// largest page size clients may request
+const MAX_PER_PAGE: i64 = 100;
+/// maximum length of search queries
+const QUERY_MAX_LENGTH: usize = 256;
+/// maximum length of language codes
+const LANG_MAX_LENGTH: usize = 16;
+/// maximum length of orth, quote, note and reading texts
+const TEXT_MAX_LENGTH: usize = 1024;
+/// maximum length of tags
+const TAG_MAX_LENGTH: usize = 64;
+/// maximum number of tags to filter by, included and excluded each
+const MAX_TAG_FILTERS: usize = 16;
+/// maximum number of tag facets returned, most frequent first
+const MAX_TAG_FACETS: i64 = 50;
 
 /// GET /word_entries list params
 #[derive(Deserialize)]
 pub struct ListWordEntriesParams {
     pub query: String,
+    /// page number for offset paging, ignored when a cursor is given
+    #[serde(default = "ListWordEntriesParams::first_page")]
     pub page: i64,
+    pub per_page: Option<i64>,
+    /// next_cursor of the previous page, for keyset paging
+    pub cursor: Option<String>,
+    /// whether to count all results, defaults to true for offset paging and false for cursor paging
+    pub count: Option<bool>,
+    #[serde(default)]
+    pub mode: SearchMode,
+    /// literal matching of the query in like and reading modes, which take it as a raw LIKE pattern otherwise
+    #[serde(rename = "match")]
+    pub match_mode: Option<MatchMode>,
+    /// restricts like mode to a single field instead of orth or quote
+    pub field: Option<SearchField>,
+    pub orth_lang: Option<String>,
+    pub quote_lang: Option<String>,
+    pub source_id: Option<i32>,
+    /// comma separated tags results must all have
+    pub tags: Option<String>,
+    /// comma separated tags results must not have
+    pub exclude_tags: Option<String>,
+    /// whether to count results per tag, defaults to the same as count
+    pub facets: Option<bool>,
+    /// whether to include the kanji in each result's orth
+    pub kanji: Option<bool>,
+}
+impl ListWordEntriesParams {
+    fn first_page() -> i64 {
+        1
+    }
+
+    /// requested page size
+    pub fn per_page(&self) -> i64 {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE)
+    }
+
+    /// whether the total result count was requested
+    pub fn with_count(&self) -> bool {
+        self.count.unwrap_or_else(|| self.cursor.is_none())
+    }
+
+    /// whether tag facets were requested
+    pub fn with_facets(&self) -> bool {
+        self.facets.unwrap_or_else(|| self.with_count())
+    }
+
+    /// whether the kanji of results were requested
+    pub fn with_kanji(&self) -> bool {
+        self.kanji.unwrap_or(false)
+    }
+
+    /// tags to include
+    pub fn tags(&self) -> Vec<String> {
+        split_tags(&self.tags)
+    }
+
+    /// tags to exclude
+    pub fn exclude_tags(&self) -> Vec<String> {
+        split_tags(&self.exclude_tags)
+    }
+}
+
+/// splits a comma separated tags param
+fn split_tags(tags: &Option<String>) -> Vec<String> {
+    match tags {
+        Some(tags) => tags.split(',').map(|tag| tag.trim().to_string()).collect(),
+        None => vec![],
+    }
+}
+
+/// checks a comma separated tags param
+fn validate_tags(validator: &mut Validator, field: &str, tags: &[String]) {
+    validator.check(tags.len() <= MAX_TAG_FILTERS, field,
+        format!("must list at most {} tags", MAX_TAG_FILTERS));
+    for tag in tags {
+        validator.text(field, tag, TAG_MAX_LENGTH);
+    }
+}
+impl Validate for ListWordEntriesParams {
+    fn validate(&self) -> Result<(), ServiceError> {
+        let mut validator = Validator::new();
+        validator.text("query", &self.query, QUERY_MAX_LENGTH);
+        // bounded so the page offset cannot overflow
+        validator.range("page", self.page, 1, i64::from(i32::MAX));
+        validator.range("per_page", self.per_page(), 1, MAX_PER_PAGE);
+        validator.check(self.match_mode.is_none() || self.mode.is_like(), "match",
+            "is only supported in like and reading modes");
+        validator.check(self.field.is_none() || self.mode == SearchMode::Like, "field",
+            "is only supported in like mode");
+        if let Some(ref orth_lang) = self.orth_lang {
+            validator.text("orth_lang", orth_lang, LANG_MAX_LENGTH);
+        }
+        if let Some(ref quote_lang) = self.quote_lang {
+            validator.text("quote_lang", quote_lang, LANG_MAX_LENGTH);
+        }
+        validate_tags(&mut validator, "tags", &self.tags());
+        validate_tags(&mut validator, "exclude_tags", &self.exclude_tags());
+        validator.finish()
+    }
 }
 /// GET /word_entries list result
 #[derive(Debug, Deserialize,Serialize)]
 pub struct ListWordEntriesResult {
     pub page: Vec<ListWordEntriesResultRecord>,
-    pub page_count: i64,
+    /// number of pages of per_page results, when counted
+    pub page_count: Option<i64>,
+    /// number of matching results, when counted
+    pub total_count: Option<i64>,
+    /// cursor for the following page, if there are more results
+    pub next_cursor: Option<String>,
+    /// number of matching results per tag, when requested
+    pub tag_facets: Option<Vec<TagFacet>>,
+}
+/// number of results with a tag
+#[derive(Debug, Deserialize, Serialize, Queryable, PartialEq)]
+pub struct TagFacet {
+    pub tag: String,
+    pub count: i64,
 }
 #[derive(Debug, Deserialize,Serialize)]
 pub struct ListWordEntriesResultRecord {
@@ -27,57 +162,603 @@ pub struct ListWordEntriesResultRecord {
     pub word_entry_notes: Vec<WordEntryNote>,
     pub word_entry_readings: Vec<WordEntryReading>,
     pub word_entry_tags: Vec<WordEntryTag>,
+    /// relevance score for ranked search modes
+    pub score: Option<f32>,
+    /// ids of the word_entry_readings matched by the reading search mode
+    #[serde(default)]
+    pub matched_reading_ids: Vec<i32>,
+    /// kanji in the orth with their readings and meanings, when requested
+    #[serde(default)]
+    pub kanji: Option<Vec<KanjiRecord>>,
 }
+
+/// reading fields for word entry request data
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WordEntryReadingData {
+    pub reading: String,
+    pub reading_tag: Option<String>,
+}
+/// POST /word_entries request data
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateWordEntryData {
+    pub orth: String,
+    pub orth_lang: String,
+    pub quote: String,
+    pub quote_lang: String,
+    #[serde(default)]
+    pub sense: i32,
+    pub group_id: i32,
+    #[serde(default)]
+    pub notes: Vec<String>,
+    #[serde(default)]
+    pub readings: Vec<WordEntryReadingData>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+/// PATCH /word_entries/{id} request data, notes, readings and tags replace
+/// the existing rows when given
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateWordEntryData {
+    #[serde(flatten)]
+    pub word_entry: WordEntryChangeset,
+    pub notes: Option<Vec<String>>,
+    pub readings: Option<Vec<WordEntryReadingData>>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// checks the texts of notes, readings and tags, naming each invalid item by index
+fn validate_joined_data(
+    validator: &mut Validator,
+    notes: &[String],
+    readings: &[WordEntryReadingData],
+    tags: &[String],
+) {
+    for (idx, note) in notes.iter().enumerate() {
+        validator.text(&format!("notes[{}]", idx), note, TEXT_MAX_LENGTH);
+    }
+    for (idx, reading) in readings.iter().enumerate() {
+        validator.text(&format!("readings[{}].reading", idx), &reading.reading, TEXT_MAX_LENGTH);
+        if let Some(ref reading_tag) = reading.reading_tag {
+            validator.text(&format!("readings[{}].reading_tag", idx), reading_tag, TAG_MAX_LENGTH);
+        }
+    }
+    for (idx, tag) in tags.iter().enumerate() {
+        validator.text(&format!("tags[{}]", idx), tag, TAG_MAX_LENGTH);
+    }
+}
+
+impl Validate for CreateWordEntryData {
+    fn validate(&self) -> Result<(), ServiceError> {
+        let mut validator = Validator::new();
+        validator.text("orth", &self.orth, TEXT_MAX_LENGTH);
+        validator.text("orth_lang", &self.orth_lang, LANG_MAX_LENGTH);
+        validator.text("quote", &self.quote, TEXT_MAX_LENGTH);
+        validator.text("quote_lang", &self.quote_lang, LANG_MAX_LENGTH);
+        validator.check(self.sense >= 0, "sense", "must not be negative");
+        validate_joined_data(&mut validator, &self.notes, &self.readings, &self.tags);
+        validator.finish()
+    }
+}
+
+impl Validate for UpdateWordEntryData {
+    fn validate(&self) -> Result<(), ServiceError> {
+        let mut validator = Validator::new();
+        let word_entry = &self.word_entry;
+        if let Some(ref orth) = word_entry.orth {
+            validator.text("orth", orth, TEXT_MAX_LENGTH);
+        }
+        if let Some(ref orth_lang) = word_entry.orth_lang {
+            validator.text("orth_lang", orth_lang, LANG_MAX_LENGTH);
+        }
+        if let Some(ref quote) = word_entry.quote {
+            validator.text("quote", quote, TEXT_MAX_LENGTH);
+        }
+        if let Some(ref quote_lang) = word_entry.quote_lang {
+            validator.text("quote_lang", quote_lang, LANG_MAX_LENGTH);
+        }
+        if let Some(sense) = word_entry.sense {
+            validator.check(sense >= 0, "sense", "must not be negative");
+        }
+        validate_joined_data(&mut validator,
+            self.notes.as_deref().unwrap_or_default(),
+            self.readings.as_deref().unwrap_or_default(),
+            self.tags.as_deref().unwrap_or_default());
+        validator.finish()
+    }
+}
+
+/// position after the last result of a page, for keyset paging
+#[derive(Debug, PartialEq)]
+struct ListCursor {
+    id: i32,
+    /// relevance score, for ranked search modes
+    score: Option<f32>,
+}
+
+impl ListCursor {
+    /// encodes the cursor as an opaque string, keeping the exact score bits
+    fn encode(&self) -> String {
+        match self.score {
+            Some(score) => format!("{}.{:08x}", self.id, score.to_bits()),
+            None => self.id.to_string(),
+        }
+    }
+
+    /// decodes a cursor made by `encode` for the same search mode
+    fn decode(cursor: &str, mode: SearchMode) -> Result<Self, ServiceError> {
+        let invalid = || ServiceError::invalid_field("cursor", "is not a valid cursor for this search");
+        let mut parts = cursor.splitn(2, '.');
+        let id = parts.next().and_then(|id| id.parse().ok()).ok_or_else(invalid)?;
+        let score = match parts.next() {
+            Some(bits) => Some(f32::from_bits(u32::from_str_radix(bits, 16).map_err(|_| invalid())?)),
+            None => None,
+        };
+        if score.is_some() != mode.is_ranked() {
+            return Err(invalid());
+        }
+        Ok(ListCursor { id, score })
+    }
+
+    /// matches the results ordered after this cursor
+    fn after_expression(&self, mode: SearchMode, query: &str) -> WordEntriesPredicate {
+        use crate::schema::word_entries;
+
+        match self.score {
+            // ranked results are ordered by score descending, then id
+            Some(score) => Box::new(
+                score_expression(mode, query).lt(score)
+                    .or(score_expression(mode, query).eq(score).and(word_entries::id.gt(self.id)))
+            ),
+            None => Box::new(word_entries::id.gt(self.id)),
+        }
+    }
+}
+
+/// builds the word_entries predicate matching the list params
+pub(crate) fn list_params_predicate(params: &ListWordEntriesParams) -> WordEntriesPredicate {
+    use diesel::dsl::not;
+    use crate::schema::{word_entries,word_entry_groups,word_entry_tags};
+
+    let mut predicate = match_expression(params.mode, &params.query, params.match_mode, params.field);
+
+    // optional language pair and dictionary source filters
+    if let Some(ref orth_lang) = params.orth_lang {
+        predicate = Box::new(predicate.and(word_entries::orth_lang.eq(orth_lang.clone())));
+    }
+    if let Some(ref quote_lang) = params.quote_lang {
+        predicate = Box::new(predicate.and(word_entries::quote_lang.eq(quote_lang.clone())));
+    }
+    if let Some(source_id) = params.source_id {
+        predicate = Box::new(predicate.and(word_entries::group_id.eq_any(
+            word_entry_groups::table
+                .select(word_entry_groups::id)
+                .filter(word_entry_groups::source_id.eq(source_id))
+                .into_boxed()
+        )));
+    }
+
+    // optional tag filters, results must have every included tag and none of the excluded
+    for tag in params.tags() {
+        predicate = Box::new(predicate.and(word_entries::id.eq_any(
+            word_entry_tags::table
+                .select(word_entry_tags::word_entry_id)
+                .filter(word_entry_tags::tag.eq(tag))
+                .into_boxed()
+        )));
+    }
+    let exclude_tags = params.exclude_tags();
+    if !exclude_tags.is_empty() {
+        predicate = Box::new(predicate.and(not(word_entries::id.eq_any(
+            word_entry_tags::table
+                .select(word_entry_tags::word_entry_id)
+                .filter(word_entry_tags::tag.eq_any(exclude_tags))
+                .into_boxed()
+        ))));
+    }
+
+    predicate
+}
+
+/// counts the entries matching the list params per tag, most frequent first
+pub(crate) fn count_tag_facets(
+    conn: &PgConnection,
+    params: &ListWordEntriesParams,
+) -> Result<Vec<TagFacet>, ServiceError> {
+    use diesel::dsl::sql;
+    use diesel::sql_types::BigInt;
+    use crate::schema::{word_entries,word_entry_tags};
+
+    let entry_count = "count(DISTINCT word_entry_tags.word_entry_id)";
+    let tag_facets = word_entry_tags::table
+        .filter(word_entry_tags::word_entry_id.eq_any(
+            filter_word_entries(params).select(word_entries::id)
+        ))
+        .group_by(word_entry_tags::tag)
+        .select((word_entry_tags::tag, sql::<BigInt>(entry_count)))
+        .order((sql::<BigInt>(entry_count).desc(), word_entry_tags::tag))
+        .limit(MAX_TAG_FACETS)
+        .get_results::<TagFacet>(conn)?;
+    Ok(tag_facets)
+}
+
+/// counts the groups with entries matching the list params per tag, most frequent first
+pub(crate) fn count_group_tag_facets(
+    conn: &PgConnection,
+    params: &ListWordEntriesParams,
+) -> Result<Vec<TagFacet>, ServiceError> {
+    use diesel::dsl::sql;
+    use diesel::sql_types::BigInt;
+    use crate::schema::{word_entries,word_entry_tags};
+
+    let group_count = "count(DISTINCT word_entries.group_id)";
+    let tag_facets = word_entry_tags::table
+        .inner_join(word_entries::table.on(word_entries::id.eq(word_entry_tags::word_entry_id)))
+        .filter(word_entry_tags::word_entry_id.eq_any(
+            filter_word_entries(params).select(word_entries::id)
+        ))
+        .group_by(word_entry_tags::tag)
+        .select((word_entry_tags::tag, sql::<BigInt>(group_count)))
+        .order((sql::<BigInt>(group_count).desc(), word_entry_tags::tag))
+        .limit(MAX_TAG_FACETS)
+        .get_results::<TagFacet>(conn)?;
+    Ok(tag_facets)
+}
+
+/// builds the word_entries query matching the list params
+pub(crate) fn filter_word_entries(params: &ListWordEntriesParams) -> crate::schema::word_entries::BoxedQuery<'_, Pg> {
+    crate::schema::word_entries::table
+        .filter(list_params_predicate(params))
+        .into_boxed()
+}
+
+/// marks which readings of each record matched a search of readings, for highlighting
+pub(crate) fn mark_matched_readings(
+    conn: &PgConnection,
+    params: &ListWordEntriesParams,
+    records: &mut [ListWordEntriesResultRecord],
+) -> Result<(), ServiceError> {
+    use crate::schema::word_entry_readings;
+
+    let readings_predicate = match matched_readings_expression(
+        params.mode, &params.query, params.match_mode, params.field) {
+        Some(readings_predicate) => readings_predicate,
+        None => return Ok(()),
+    };
+
+    let matched_ids = word_entry_readings::table
+        .select(word_entry_readings::id)
+        .filter(word_entry_readings::word_entry_id
+            .eq_any(records.iter().map(|e| e.word_entry.id).collect::<Vec<_>>()))
+        .filter(readings_predicate)
+        .get_results::<i32>(conn)?;
+    for record in records.iter_mut() {
+        record.matched_reading_ids = record.word_entry_readings.iter()
+            .map(|r| r.id)
+            .filter(|reading_id| matched_ids.contains(reading_id))
+            .collect();
+    }
+    Ok(())
+}
+
+/// loads joined notes, readings and tags for a list of word entries
+pub(crate) fn load_word_entry_records(
+    conn: &PgConnection,
+    word_entries_items: Vec<WordEntry>,
+) -> Result<Vec<ListWordEntriesResultRecord>, ServiceError> {
+    let word_entry_notes_items = WordEntryNote::belonging_to(&word_entries_items)
+        .order(crate::schema::word_entry_notes::id)
+        .get_results::<WordEntryNote>(conn)?
+        .grouped_by(&word_entries_items);
+    let word_entry_readings_items = WordEntryReading::belonging_to(&word_entries_items)
+        .order(crate::schema::word_entry_readings::id)
+        .get_results::<WordEntryReading>(conn)?
+        .grouped_by(&word_entries_items);
+    let word_entry_tags_items = WordEntryTag::belonging_to(&word_entries_items)
+        .order(crate::schema::word_entry_tags::id)
+        .get_results::<WordEntryTag>(conn)?
+        .grouped_by(&word_entries_items);
+
+    // zip items
+    Ok(multizip((word_entries_items, word_entry_notes_items,
+        word_entry_readings_items, word_entry_tags_items)).map({|t|
+            ListWordEntriesResultRecord {
+                word_entry: t.0,
+                word_entry_notes: t.1,
+                word_entry_readings: t.2,
+                word_entry_tags: t.3,
+                score: None,
+                matched_reading_ids: Vec::new(),
+                kanji: None,
+            }
+        }).collect::<Vec<_>>())
+}
+
+/// loads a single word entry record with joined notes, readings and tags
+fn load_word_entry_record(
+    conn: &PgConnection,
+    word_entry_id: i32,
+) -> Result<ListWordEntriesResultRecord, ServiceError> {
+    use crate::schema::word_entries;
+
+    let word_entry = word_entries::table.find(word_entry_id)
+        .get_result::<WordEntry>(conn)
+        .optional()?
+        .ok_or(ServiceError::NotFound)?;
+
+    load_word_entry_records(conn, vec![word_entry])?.pop()
+        .ok_or(ServiceError::InternalServerError)
+}
+
+/// checks the word_entry_groups record for a group_id exists
+fn check_group_exists(conn: &PgConnection, group_id: i32) -> Result<(), ServiceError> {
+    use crate::schema::word_entry_groups;
+
+    word_entry_groups::table.find(group_id)
+        .get_result::<WordEntryGroup>(conn)
+        .optional()?
+        .map(|_| ())
+        .ok_or_else(|| ServiceError::BadRequest(format!("word_entry_group {} does not exist", group_id)))
+}
+
+/// inserts word_entry_notes records for a word entry
+fn insert_notes(conn: &PgConnection, word_entry_id: i32, notes: Vec<String>) -> Result<(), ServiceError> {
+    use crate::schema::word_entry_notes;
+
+    let new_records = notes.into_iter()
+        .map(|note| NewWordEntryNote { word_entry_id, note })
+        .collect::<Vec<_>>();
+    diesel::insert_into(word_entry_notes::table).values(&new_records).execute(conn)?;
+    Ok(())
+}
+
+/// inserts word_entry_readings records for a word entry, with search keys for its orth_lang
+fn insert_readings(conn: &PgConnection, word_entry_id: i32, orth_lang: &str, readings: Vec<WordEntryReadingData>) -> Result<(), ServiceError> {
+    use crate::schema::word_entry_readings;
+
+    let new_records = readings.into_iter()
+        .map(|r| NewWordEntryReading {
+            word_entry_id,
+            search_key: reading_search_key(orth_lang, &r.reading),
+            reading: r.reading,
+            reading_tag: r.reading_tag,
+        })
+        .collect::<Vec<_>>();
+    diesel::insert_into(word_entry_readings::table).values(&new_records).execute(conn)?;
+    Ok(())
+}
+
+/// inserts word_entry_tags records for a word entry
+fn insert_tags(conn: &PgConnection, word_entry_id: i32, tags: Vec<String>) -> Result<(), ServiceError> {
+    use crate::schema::word_entry_tags;
+
+    let new_records = tags.into_iter()
+        .map(|tag| NewWordEntryTag { word_entry_id, tag })
+        .collect::<Vec<_>>();
+    diesel::insert_into(word_entry_tags::table).values(&new_records).execute(conn)?;
+    Ok(())
+}
+
 /// GET /word_entries list endpoint
 pub async fn list_word_entries(
     params: web::Query<ListWordEntriesParams>,
     pool: web::Data<DbPool>,
 ) -> Result<HttpResponse, ServiceError> {
+    params.validate()?;
+
     let res = web::block(move || {
         use diesel::dsl::count_star;
         use crate::schema::word_entries;
-        use crate::schema::word_entries::dsl::{orth,quote};
 
         let conn: &PgConnection = &pool.get().unwrap();
 
-        // query word entries page
-        let offset = (params.page - 1) * PER_PAGE;
-        let query = &params.query;
-        let word_entries_items = word_entries::table
-            .filter(orth.ilike(query.clone()).or(quote.ilike(query.clone())))
-            .order(word_entries::id).offset(offset).limit(PER_PAGE)
-            .get_results::<WordEntry>(conn)?;
-        let count: i64 = word_entries::table
-            .select(count_star())
-            .filter(orth.ilike(query.clone()).or(quote.ilike(query.clone())))
-            .first(conn)?;
+        let per_page = params.per_page();
+        let cursor = match params.cursor {
+            Some(ref cursor) => Some(ListCursor::decode(cursor, params.mode)?),
+            None => None,
+        };
+
+        // query word entries page, ranked modes order by relevance first
+        let mut page_query = filter_word_entries(&params)
+            .select((word_entries::all_columns, score_expression(params.mode, &params.query)));
+        if params.mode.is_ranked() {
+            page_query = page_query.order(score_expression(params.mode, &params.query).desc());
+        }
+        page_query = page_query.then_order_by(word_entries::id);
+        page_query = match cursor {
+            Some(ref cursor) => page_query.filter(cursor.after_expression(params.mode, &params.query)),
+            None => page_query.offset((params.page - 1) * per_page),
+        };
+
+        // fetch one extra row to know whether there is a next page
+        let mut rows = page_query
+            .limit(per_page + 1)
+            .get_results::<(WordEntry, Option<f32>)>(conn)?;
+        let next_cursor = if rows.len() as i64 > per_page {
+            rows.truncate(per_page as usize);
+            rows.last().map(|(word_entry, score)| ListCursor { id: word_entry.id, score: *score }.encode())
+        } else {
+            None
+        };
+        let (word_entries_items, scores): (Vec<WordEntry>, Vec<Option<f32>>) = rows.into_iter().unzip();
+
+        let total_count = if params.with_count() {
+            Some(filter_word_entries(&params).select(count_star()).first::<i64>(conn)?)
+        } else {
+            None
+        };
+        let tag_facets = if params.with_facets() {
+            Some(count_tag_facets(conn, &params)?)
+        } else {
+            None
+        };
 
         // get joined records
-        let word_entry_notes_items = WordEntryNote::belonging_to(&word_entries_items)
-            .get_results::<WordEntryNote>(conn)?
-            .grouped_by(&word_entries_items);
-        let word_entry_readings_items = WordEntryReading::belonging_to(&word_entries_items)
-            .get_results::<WordEntryReading>(conn)?
-            .grouped_by(&word_entries_items);
-        let word_entry_tags_items = WordEntryTag::belonging_to(&word_entries_items)
-            .get_results::<WordEntryTag>(conn)?
-            .grouped_by(&word_entries_items);
-
-        // zip items
-        let entries = multizip((word_entries_items, word_entry_notes_items,
-            word_entry_readings_items, word_entry_tags_items)).map({|t|
-                ListWordEntriesResultRecord {
-                    word_entry: t.0,
-                    word_entry_notes: t.1,
-                    word_entry_readings: t.2,
-                    word_entry_tags: t.3,
-                }
-            }).collect::<Vec<_>>();
+        let mut entries = load_word_entry_records(conn, word_entries_items)?;
+        for (entry, score) in entries.iter_mut().zip(scores) {
+            entry.score = score;
+        }
+
+        // mark which readings matched for highlighting
+        mark_matched_readings(conn, &params, &mut entries)?;
+        if params.with_kanji() {
+            expand_kanji(conn, &mut entries)?;
+        }
 
         // result
-        return Ok(ListWordEntriesResult {
+        Ok(ListWordEntriesResult {
             page: entries,
-            page_count: (count as f64 / PER_PAGE as f64).ceil() as i64,
+            page_count: total_count.map(|count| (count as f64 / per_page as f64).ceil() as i64),
+            total_count,
+            next_cursor,
+            tag_facets,
+        })
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Ok().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// GET /word_entries/{id}
+pub async fn get_word_entry(
+    _session_user: SessionUser,
+    word_entry_id: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let res = web::block(move || {
+        let conn: &PgConnection = &pool.get().unwrap();
+        load_word_entry_record(conn, word_entry_id.into_inner())
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Ok().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// POST /word_entries
+pub async fn create_word_entry(
+    _editor_user: EditorUser,
+    entry_data: web::Json<CreateWordEntryData>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let entry_data_inner: CreateWordEntryData = entry_data.into_inner();
+    entry_data_inner.validate()?;
+
+    let res = web::block(move || {
+        use crate::schema::word_entries;
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        conn.transaction::<_, ServiceError, _>(|| {
+            check_group_exists(conn, entry_data_inner.group_id)?;
+
+            // insert word_entries record
+            let new_entry = NewWordEntry {
+                orth: entry_data_inner.orth,
+                orth_lang: entry_data_inner.orth_lang,
+                quote: entry_data_inner.quote,
+                quote_lang: entry_data_inner.quote_lang,
+                sense: entry_data_inner.sense,
+                group_id: entry_data_inner.group_id,
+            };
+            let created_entry: WordEntry = diesel::insert_into(word_entries::table)
+                .values(&new_entry)
+                .get_result(conn)?;
+
+            // insert joined records
+            insert_notes(conn, created_entry.id, entry_data_inner.notes)?;
+            insert_readings(conn, created_entry.id, &created_entry.orth_lang, entry_data_inner.readings)?;
+            insert_tags(conn, created_entry.id, entry_data_inner.tags)?;
+
+            load_word_entry_record(conn, created_entry.id)
+        })
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Created().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// PATCH /word_entries/{id}
+pub async fn update_word_entry(
+    _editor_user: EditorUser,
+    word_entry_id: web::Path<i32>,
+    entry_data: web::Json<UpdateWordEntryData>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let word_entry_id = word_entry_id.into_inner();
+    let entry_data_inner: UpdateWordEntryData = entry_data.into_inner();
+    entry_data_inner.validate()?;
+
+    let res = web::block(move || {
+        use crate::schema::{word_entries,word_entry_notes,word_entry_readings,word_entry_tags};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        conn.transaction::<_, ServiceError, _>(|| {
+            // lock the existing record for the rest of the transaction
+            let existing_entry = word_entries::table.find(word_entry_id)
+                .for_update()
+                .get_result::<WordEntry>(conn)
+                .optional()?
+                .ok_or(ServiceError::NotFound)?;
+
+            // update word_entries fields, diesel rejects empty changesets
+            if !entry_data_inner.word_entry.is_empty() {
+                if let Some(group_id) = entry_data_inner.word_entry.group_id {
+                    check_group_exists(conn, group_id)?;
+                }
+                diesel::update(word_entries::table.find(word_entry_id))
+                    .set(&entry_data_inner.word_entry)
+                    .execute(conn)?;
+            }
+
+            // replace joined records when given
+            if let Some(notes) = entry_data_inner.notes {
+                diesel::delete(word_entry_notes::table
+                    .filter(word_entry_notes::word_entry_id.eq(word_entry_id)))
+                    .execute(conn)?;
+                insert_notes(conn, word_entry_id, notes)?;
+            }
+            // readings are re-keyed if orth_lang changed
+            let orth_lang = entry_data_inner.word_entry.orth_lang.clone()
+                .unwrap_or(existing_entry.orth_lang);
+            if let Some(readings) = entry_data_inner.readings {
+                diesel::delete(word_entry_readings::table
+                    .filter(word_entry_readings::word_entry_id.eq(word_entry_id)))
+                    .execute(conn)?;
+                insert_readings(conn, word_entry_id, &orth_lang, readings)?;
+            } else if entry_data_inner.word_entry.orth_lang.is_some() {
+                let existing_readings = word_entry_readings::table
+                    .filter(word_entry_readings::word_entry_id.eq(word_entry_id))
+                    .get_results::<WordEntryReading>(conn)?;
+                for existing_reading in existing_readings {
+                    diesel::update(word_entry_readings::table.find(existing_reading.id))
+                        .set(word_entry_readings::search_key
+                            .eq(reading_search_key(&orth_lang, &existing_reading.reading)))
+                        .execute(conn)?;
+                }
+            }
+            if let Some(tags) = entry_data_inner.tags {
+                diesel::delete(word_entry_tags::table
+                    .filter(word_entry_tags::word_entry_id.eq(word_entry_id)))
+                    .execute(conn)?;
+                insert_tags(conn, word_entry_id, tags)?;
+            }
+
+            load_word_entry_record(conn, word_entry_id)
         })
     }).await;
 
@@ -88,4 +769,47 @@ pub async fn list_word_entries(
             BlockingError::Canceled => Err(ServiceError::InternalServerError),
         },
     }
-}
\ No newline at end of file
+}
+
+/// DELETE /word_entries/{id}
+pub async fn delete_word_entry(
+    _editor_user: EditorUser,
+    word_entry_id: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let word_entry_id = word_entry_id.into_inner();
+
+    let res = web::block(move || {
+        use crate::schema::{word_entries,word_entry_notes,word_entry_readings,word_entry_tags};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        conn.transaction::<_, ServiceError, _>(|| {
+            // delete joined records, then the entry itself
+            diesel::delete(word_entry_notes::table
+                .filter(word_entry_notes::word_entry_id.eq(word_entry_id)))
+                .execute(conn)?;
+            diesel::delete(word_entry_readings::table
+                .filter(word_entry_readings::word_entry_id.eq(word_entry_id)))
+                .execute(conn)?;
+            diesel::delete(word_entry_tags::table
+                .filter(word_entry_tags::word_entry_id.eq(word_entry_id)))
+                .execute(conn)?;
+            let deleted_count = diesel::delete(word_entries::table.find(word_entry_id))
+                .execute(conn)?;
+
+            if deleted_count == 0 {
+                return Err(ServiceError::NotFound);
+            }
+            Ok(())
+        })
+    }).await;
+
+    match res {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
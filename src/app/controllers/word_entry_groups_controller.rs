@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use actix_web::{error::BlockingError, web, HttpResponse};
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use serde::{Deserialize,Serialize};
+
+use crate::app::controllers::kanji_controller::expand_kanji;
+use crate::app::controllers::word_entries_controller::{count_group_tag_facets, filter_word_entries,
+    list_params_predicate, load_word_entry_records, mark_matched_readings, ListWordEntriesParams,
+    ListWordEntriesResultRecord, TagFacet};
+use crate::app::database::DbPool;
+use crate::app::errors::ServiceError;
+use crate::app::models::{Source,WordEntry,WordEntryGroup};
+use crate::app::search::{max_score, score_expression};
+use crate::app::validation::Validate;
+
+/// GET /word_entry_groups list result
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListWordEntryGroupsResult {
+    pub page: Vec<ListWordEntryGroupsResultRecord>,
+    /// number of pages of per_page groups, when counted
+    pub page_count: Option<i64>,
+    /// number of matching groups, when counted
+    pub total_count: Option<i64>,
+    /// number of matching groups per tag, when requested
+    pub tag_facets: Option<Vec<TagFacet>>,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListWordEntryGroupsResultRecord {
+    pub word_entry_group: WordEntryGroup,
+    /// best relevance score of the group's matching entries for ranked search modes
+    pub score: Option<f32>,
+    /// matching senses and orth variants of the group, ordered by sense
+    pub word_entries: Vec<ListWordEntriesResultRecord>,
+}
+
+/// GET /word_entry_groups/{id} result, a complete dictionary headword
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WordEntryGroupResult {
+    pub word_entry_group: WordEntryGroup,
+    pub source: Source,
+    /// all senses of the group, ordered by sense
+    pub word_entries: Vec<ListWordEntriesResultRecord>,
+}
+
+/// GET /word_entry_groups list endpoint, searches like GET /word_entries with one result per group
+pub async fn list_word_entry_groups(
+    params: web::Query<ListWordEntriesParams>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    params.validate()?;
+    // groups are ordered by an aggregate score, which keyset paging cannot filter on here
+    if params.cursor.is_some() {
+        return Err(ServiceError::invalid_field("cursor", "is not supported for grouped results, use page"));
+    }
+    let per_page = params.per_page();
+
+    let res = web::block(move || {
+        use crate::schema::{word_entries,word_entry_groups};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        // query page of matching group ids, ranked modes order by best relevance first
+        let offset = (params.page - 1) * per_page;
+        let mut page_query = word_entries::table
+            .filter(list_params_predicate(&params))
+            .group_by(word_entries::group_id)
+            .select((word_entries::group_id, max_score(score_expression(params.mode, &params.query))))
+            .into_boxed();
+        if params.mode.is_ranked() {
+            page_query = page_query.order(max_score(score_expression(params.mode, &params.query)).desc());
+        }
+        let group_scores = page_query
+            .then_order_by(word_entries::group_id).offset(offset).limit(per_page)
+            .get_results::<(i32, Option<f32>)>(conn)?;
+        let group_ids = group_scores.iter().map(|(group_id, _)| *group_id).collect::<Vec<_>>();
+        let total_count = if params.with_count() {
+            Some(filter_word_entries(&params)
+                .select(sql::<BigInt>("count(DISTINCT word_entries.group_id)"))
+                .first::<i64>(conn)?)
+        } else {
+            None
+        };
+        let tag_facets = if params.with_facets() {
+            Some(count_group_tag_facets(conn, &params)?)
+        } else {
+            None
+        };
+
+        // matching entries of the page's groups
+        let (word_entries_items, scores): (Vec<WordEntry>, Vec<Option<f32>>) = filter_word_entries(&params)
+            .filter(word_entries::group_id.eq_any(&group_ids))
+            .select((word_entries::all_columns, score_expression(params.mode, &params.query)))
+            .order((word_entries::group_id, word_entries::sense, word_entries::id))
+            .get_results::<(WordEntry, Option<f32>)>(conn)?
+            .into_iter().unzip();
+        let mut entries = load_word_entry_records(conn, word_entries_items)?;
+        for (entry, score) in entries.iter_mut().zip(scores) {
+            entry.score = score;
+        }
+        mark_matched_readings(conn, &params, &mut entries)?;
+        if params.with_kanji() {
+            expand_kanji(conn, &mut entries)?;
+        }
+
+        // nest entries under their groups, in page order
+        let mut groups_by_id = word_entry_groups::table
+            .filter(word_entry_groups::id.eq_any(&group_ids))
+            .get_results::<WordEntryGroup>(conn)?
+            .into_iter()
+            .map(|group| (group.id, group))
+            .collect::<HashMap<_, _>>();
+        let mut entries_by_group_id: HashMap<i32, Vec<ListWordEntriesResultRecord>> = HashMap::new();
+        for entry in entries {
+            entries_by_group_id.entry(entry.word_entry.group_id).or_default().push(entry);
+        }
+        let page = group_scores.into_iter()
+            .filter_map(|(group_id, score)| Some(ListWordEntryGroupsResultRecord {
+                word_entry_group: groups_by_id.remove(&group_id)?,
+                score,
+                word_entries: entries_by_group_id.remove(&group_id).unwrap_or_default(),
+            }))
+            .collect::<Vec<_>>();
+
+        // result
+        Ok(ListWordEntryGroupsResult {
+            page,
+            page_count: total_count.map(|count| (count as f64 / per_page as f64).ceil() as i64),
+            total_count,
+            tag_facets,
+        })
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Ok().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
+
+/// GET /word_entry_groups/{id}
+pub async fn get_word_entry_group(
+    group_id: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let res = web::block(move || {
+        use crate::schema::{sources,word_entries,word_entry_groups};
+
+        let conn: &PgConnection = &pool.get().unwrap();
+
+        let word_entry_group = word_entry_groups::table.find(group_id.into_inner())
+            .get_result::<WordEntryGroup>(conn)
+            .optional()?
+            .ok_or(ServiceError::NotFound)?;
+        let source = sources::table.find(word_entry_group.source_id)
+            .get_result::<Source>(conn)?;
+
+        // orth variants of the same sense keep insertion order
+        let word_entries_items = word_entries::table
+            .filter(word_entries::group_id.eq(word_entry_group.id))
+            .order((word_entries::sense, word_entries::id))
+            .get_results::<WordEntry>(conn)?;
+
+        Ok(WordEntryGroupResult {
+            word_entry_group,
+            source,
+            word_entries: load_word_entry_records(conn, word_entries_items)?,
+        })
+    }).await;
+
+    match res {
+        Ok(result) => Ok(HttpResponse::Ok().json(&result)),
+        Err(err) => match err {
+            BlockingError::Error(service_error) => Err(service_error),
+            BlockingError::Canceled => Err(ServiceError::InternalServerError),
+        },
+    }
+}
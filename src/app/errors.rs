@@ -1,6 +1,7 @@
-use actix_web::{error::ResponseError, HttpResponse};
+use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
 use derive_more::Display;
 use diesel::result::{DatabaseErrorKind, Error as DBError};
+use serde::{Deserialize, Serialize};
 use std::convert::From;
 
 /// Error types that can propagate through the application
@@ -14,39 +15,141 @@ pub enum ServiceError {
 
     #[display(fmt = "Unauthorized")]
     Unauthorized,
+
+    #[display(fmt = "Forbidden")]
+    Forbidden,
+
+    #[display(fmt = "Not Found")]
+    NotFound,
+
+    #[display(fmt = "Conflict: {}", _0)]
+    Conflict(String),
+
+    #[display(fmt = "Validation failed")]
+    Validation(Vec<FieldError>),
+
+    #[display(fmt = "Too Many Requests, retry after {} seconds", _0)]
+    TooManyRequests(i64),
+}
+
+/// invalid field of a request, listed in the details of Validation errors
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// JSON body of every error response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    /// stable machine-readable error code, e.g. `not_found`
+    pub code: String,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+    /// set by the request id middleware, matches the `X-Request-Id` response header
+    pub request_id: Option<String>,
+}
+
+impl ErrorResponse {
+    /// builds the error envelope for errors not raised as a ServiceError, e.g. extractor errors
+    pub fn from_status(status: StatusCode, message: String, request_id: Option<String>) -> Self {
+        let code = status.canonical_reason().unwrap_or("Error")
+            .to_lowercase()
+            .replace(' ', "_");
+        ErrorResponse { code, message, details: None, request_id }
+    }
+}
+
+impl ServiceError {
+    /// shorthand for a Validation error with a single invalid field
+    pub fn invalid_field<S: Into<String>, T: Into<String>>(field: S, message: T) -> Self {
+        ServiceError::Validation(vec![FieldError { field: field.into(), message: message.into() }])
+    }
+
+    /// stable machine-readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            ServiceError::InternalServerError => "internal_server_error",
+            ServiceError::BadRequest(_) => "bad_request",
+            ServiceError::Unauthorized => "unauthorized",
+            ServiceError::Forbidden => "forbidden",
+            ServiceError::NotFound => "not_found",
+            ServiceError::Conflict(_) => "conflict",
+            ServiceError::Validation(_) => "validation_failed",
+            ServiceError::TooManyRequests(_) => "too_many_requests",
+        }
+    }
+
+    /// builds the error envelope for this error
+    pub fn to_error_body(&self, request_id: Option<String>) -> ErrorResponse {
+        let message = match self {
+            ServiceError::InternalServerError => "Internal Server Error, Please try later".to_string(),
+            ServiceError::BadRequest(ref message) => message.clone(),
+            ServiceError::Conflict(ref message) => message.clone(),
+            _ => self.to_string(),
+        };
+        let details = match self {
+            ServiceError::Validation(ref field_errors) => serde_json::to_value(field_errors).ok(),
+            ServiceError::TooManyRequests(retry_after_seconds) =>
+                Some(serde_json::json!({ "retry_after": retry_after_seconds })),
+            _ => None,
+        };
+        ErrorResponse { code: self.code().to_string(), message, details, request_id }
+    }
+
+    /// Converts ServiceError into an HttpResponse with the given request id in its body
+    pub fn to_response(&self, request_id: Option<String>) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if let ServiceError::TooManyRequests(retry_after_seconds) = self {
+            response.header("Retry-After", retry_after_seconds.to_string());
+        }
+        response.json(self.to_error_body(request_id))
+    }
 }
 
 impl ResponseError for ServiceError {
-    /// Converts ServiceError into actix-web HttpResponse objects
-    fn error_response(&self) -> HttpResponse {
+    /// HTTP status of each error type
+    fn status_code(&self) -> StatusCode {
         match self {
-            ServiceError::InternalServerError => HttpResponse::InternalServerError()
-                .json("Internal Server Error, Please try later"),
-            ServiceError::BadRequest(ref message) => {
-                HttpResponse::BadRequest().json(message)
-            }
-            ServiceError::Unauthorized => {
-                HttpResponse::Unauthorized().json("Unauthorized")
-            }
+            ServiceError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            ServiceError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ServiceError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ServiceError::Forbidden => StatusCode::FORBIDDEN,
+            ServiceError::NotFound => StatusCode::NOT_FOUND,
+            ServiceError::Conflict(_) => StatusCode::CONFLICT,
+            ServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            ServiceError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
         }
     }
+
+    /// Converts ServiceError into actix-web HttpResponse objects
+    fn error_response(&self) -> HttpResponse {
+        self.to_response(None)
+    }
 }
 
 impl From<DBError> for ServiceError {
     /// Converts DBError from Diesel into application ServiceError type
     fn from(error: DBError) -> ServiceError {
-        // Right now we just care about UniqueViolation from diesel
-        // But this would be helpful to easily map errors as our app grows
         match error {
+            DBError::NotFound => ServiceError::NotFound,
             DBError::DatabaseError(kind, info) => {
-                if let DatabaseErrorKind::UniqueViolation = kind {
-                    let message =
-                        info.details().unwrap_or_else(|| info.message()).to_string();
-                    return ServiceError::BadRequest(message);
+                let message = info.details().unwrap_or_else(|| info.message()).to_string();
+                match kind {
+                    DatabaseErrorKind::UniqueViolation | DatabaseErrorKind::ForeignKeyViolation => {
+                        log::info!("database constraint violation: {}", info.message());
+                        ServiceError::Conflict(message)
+                    }
+                    _ => {
+                        log::error!("database error: {}", info.message());
+                        ServiceError::InternalServerError
+                    }
                 }
+            }
+            _ => {
+                log::error!("database error: {}", error);
                 ServiceError::InternalServerError
             }
-            _ => ServiceError::InternalServerError,
         }
     }
-}
\ No newline at end of file
+}
@@ -9,7 +9,7 @@ pub fn get_identity_service() -> IdentityService<CookieIdentityPolicy> {
             .name("auth")
             .path("/")
             .domain(config::APP_DOMAIN.as_str())
-            .max_age_time(chrono::Duration::days(1))
+            .max_age_time(*config::SESSION_MAX_AGE)
             .secure(true)
     )
 }
@@ -1,19 +1,115 @@
+use std::net::IpAddr;
+use std::ops::Deref;
 use std::pin::Pin;
 use actix_identity::Identity;
-use actix_web::{dev::Payload, web, Error, FromRequest, HttpRequest};
+use actix_web::{dev::Payload, http::header, web, Error, FromRequest, HttpRequest};
 use chrono::{Utc};
 use diesel::prelude::*;
 use diesel::PgConnection;
 use futures::future::Future;
 
+use crate::app::config;
 use crate::app::database::DbPool;
 use crate::app::errors::ServiceError;
-use crate::app::models::{Session, SlimUser, User};
+use crate::app::models::{ApiToken, Session, SlimUser, User};
+use crate::app::security::hash_token;
 
 // extend SlimUser type for use as middleware
 pub type SessionUser = SlimUser;
 
-/// middleware for getting SlimUser data from cookie identity session
+/// deletes all idle or too-old sessions, returning the number of sessions removed
+pub fn purge_expired_sessions(conn: &PgConnection) -> QueryResult<usize> {
+    use crate::schema::sessions::dsl::{created_at, last_accessed_at, sessions};
+
+    let now = Utc::now();
+    diesel::delete(sessions.filter(
+        last_accessed_at.lt(now - *config::SESSION_IDLE_TIMEOUT)
+            .or(created_at.lt(now - *config::SESSION_MAX_AGE))
+    )).execute(conn)
+}
+
+/// the client ip behind trusted proxies, the last X-Forwarded-For address that is not itself a trusted proxy
+fn forwarded_client_ip(peer_ip: IpAddr, forwarded_for: Option<&str>, trusted_proxies: &[IpAddr]) -> IpAddr {
+    let mut client_ip = peer_ip;
+    // proxies append the address they received a request from, so only trailing entries can be trusted
+    for forwarded_ip in forwarded_for.unwrap_or_default().rsplit(',') {
+        if !trusted_proxies.contains(&client_ip) {
+            break;
+        }
+        match forwarded_ip.trim().parse() {
+            Ok(forwarded_ip) => client_ip = forwarded_ip,
+            Err(_) => break,
+        }
+    }
+    client_ip
+}
+
+/// gets the client ip of a request, only honoring X-Forwarded-For from trusted proxies
+pub fn client_ip(req: &HttpRequest) -> Option<String> {
+    let peer_ip = req.peer_addr()?.ip();
+    let forwarded_for = req.headers().get("X-Forwarded-For")
+        .and_then(|value| value.to_str().ok());
+    Some(forwarded_client_ip(peer_ip, forwarded_for, &config::TRUSTED_PROXIES).to_string())
+}
+
+/// finds the user of a personal api token, noting when the token was last used
+fn find_api_token_user(conn: &PgConnection, bearer_token: &str) -> Result<SlimUser, ServiceError> {
+    use crate::schema::api_tokens::dsl::{api_tokens, last_used_at, token_hash};
+    use crate::schema::users::dsl::{users};
+
+    let api_token = api_tokens
+        .filter(token_hash.eq(hash_token(bearer_token)))
+        .get_result::<ApiToken>(conn)
+        .map_err(|_| ServiceError::Unauthorized)?;
+
+    let user = users
+        .find(api_token.user_id)
+        .get_result::<User>(conn)
+        .map_err(|_| ServiceError::Unauthorized)?;
+
+    diesel::update(api_tokens.find(api_token.id))
+        .set(last_used_at.eq(Utc::now()))
+        .execute(conn)?;
+
+    Ok(SlimUser::from(user))
+}
+
+/// finds the user of a cookie identity session, noting when and from where it was last accessed
+fn find_session_user(conn: &PgConnection, identity: &str, remote_ip: Option<String>) -> Result<SlimUser, ServiceError> {
+    use crate::schema::sessions::dsl::{accessed_by_client_ip, last_accessed_at, sessions};
+    use crate::schema::users::dsl::{id, users};
+
+    // try finding user session
+    let user_session = sessions
+      .find(identity)
+      .get_result::<Session>(conn)
+      .map_err(|_| ServiceError::Unauthorized)?;
+
+    // expired sessions are removed on access
+    if user_session.is_expired() {
+        let _ = diesel::delete(sessions.find(identity)).execute(conn);
+        return Err(ServiceError::Unauthorized);
+    }
+
+    // try finding user
+    let user = users
+        .filter(id.eq(&user_session.user_id))
+        .get_result::<User>(conn)
+        .map_err(|_| ServiceError::Unauthorized)?;
+
+    // update session last-access details
+    diesel::update(sessions.find(identity))
+      .set((
+          last_accessed_at.eq(Utc::now()),
+          accessed_by_client_ip.eq(remote_ip)
+      ))
+      .execute(conn)
+      .expect(&format!("Unable to update session {:?}", identity));
+
+    Ok(SlimUser::from(user))
+}
+
+/// middleware for getting SlimUser data from an `Authorization: Bearer` api token or cookie identity session
 impl FromRequest for SessionUser {
     type Config = ();
     type Error = Error;
@@ -25,39 +121,26 @@ impl FromRequest for SessionUser {
         let pool_fut = web::Data::<DbPool>::from_request(req, pl);
 
         // get remote ip address from request
-        let remote_ip = req.connection_info().remote().map(|s| s.to_string());
+        let remote_ip = client_ip(req);
+
+        // get api token from authorization header, if any
+        let bearer_token = req.headers().get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| value.starts_with("Bearer "))
+            .map(|value| value["Bearer ".len()..].trim().to_string());
 
         Box::pin(async move {
-            use crate::schema::sessions::dsl::{accessed_by_client_ip,
-                last_accessed_at, token, sessions};
-            use crate::schema::users::dsl::{id, users};
+            // api tokens take precedence over the cookie, and are not checked against it
+            if let Some(bearer_token) = bearer_token {
+                let pool = pool_fut.await?;
+                let conn: &PgConnection = &pool.get().unwrap();
+                return Ok(find_api_token_user(conn, &bearer_token)?);
+            }
 
             if let Some(identity) = fut.await?.identity() {
                 let pool = pool_fut.await?;
                 let conn: &PgConnection = &pool.get().unwrap();
-
-                // try finding user session
-                let user_session = sessions
-                  .filter(token.eq(&identity))
-                  .get_result::<Session>(conn)
-                  .map_err(|_| ServiceError::Unauthorized)?;
-
-                // try finding user
-                let user = users
-                    .filter(id.eq(&user_session.user_id))
-                    .get_result::<User>(conn)
-                    .map_err(|_| ServiceError::Unauthorized)?;
-
-                // update session last-access details
-                diesel::update(sessions.find(&identity))
-                  .set((
-                      last_accessed_at.eq(Utc::now()),
-                      accessed_by_client_ip.eq(remote_ip)
-                  ))
-                  .execute(conn)
-                  .expect(&format!("Unable to update session {:?}", &identity));
-
-                return Ok(SlimUser::from(user))
+                Ok(find_session_user(conn, &identity, remote_ip)?)
             } else {
                 // Unauthorized if no identity found
                 Err(ServiceError::Unauthorized.into())
@@ -65,3 +148,65 @@ impl FromRequest for SessionUser {
         })
     }
 }
+
+/// SessionUser signed in with a cookie identity session, for actions api tokens must not perform
+#[derive(Debug)]
+pub struct CookieSessionUser {
+    pub user: SessionUser,
+    /// token of the session the request was made with
+    pub session_token: String,
+}
+
+impl Deref for CookieSessionUser {
+    type Target = SessionUser;
+
+    fn deref(&self) -> &SessionUser {
+        &self.user
+    }
+}
+
+/// middleware for requiring a cookie identity session, ignoring any api token
+impl FromRequest for CookieSessionUser {
+    type Config = ();
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<CookieSessionUser, Error>>>>;
+
+    fn from_request(req: &HttpRequest, pl: &mut Payload) -> Self::Future {
+        // get identity session_token and remote ip address from request
+        let fut = Identity::from_request(req, pl);
+        let pool_fut = web::Data::<DbPool>::from_request(req, pl);
+        let remote_ip = client_ip(req);
+
+        Box::pin(async move {
+            // Unauthorized if no identity found
+            let session_token = fut.await?.identity().ok_or(ServiceError::Unauthorized)?;
+            let pool = pool_fut.await?;
+            let conn: &PgConnection = &pool.get().unwrap();
+            let user = find_session_user(conn, &session_token, remote_ip)?;
+            Ok(CookieSessionUser { user, session_token })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forwarded_client_ip() {
+        let ip = |addr: &str| addr.parse::<IpAddr>().unwrap();
+        let proxies = [ip("10.0.0.1"), ip("10.0.0.2")];
+
+        // forwarding headers are ignored from untrusted peers
+        assert_eq!(forwarded_client_ip(ip("203.0.113.9"), Some("198.51.100.1"), &proxies), ip("203.0.113.9"));
+        assert_eq!(forwarded_client_ip(ip("203.0.113.9"), None, &proxies), ip("203.0.113.9"));
+
+        // trusted proxies are skipped from the right, spoofed entries before the client are ignored
+        assert_eq!(forwarded_client_ip(ip("10.0.0.1"), Some("198.51.100.1"), &proxies), ip("198.51.100.1"));
+        assert_eq!(forwarded_client_ip(ip("10.0.0.1"), Some("1.2.3.4, 198.51.100.1, 10.0.0.2"), &proxies),
+            ip("198.51.100.1"));
+
+        // malformed entries stop at the last trusted address
+        assert_eq!(forwarded_client_ip(ip("10.0.0.1"), Some("unknown"), &proxies), ip("10.0.0.1"));
+    }
+}
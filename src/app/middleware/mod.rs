@@ -1 +1,3 @@
 pub mod auth_middleware;
+pub mod role_middleware;
+pub mod request_id_middleware;
@@ -0,0 +1,67 @@
+use actix_service::Service;
+use actix_web::{body::Body, dev::{ServiceRequest, ServiceResponse}, Error, HttpResponse};
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use futures::future::Future;
+
+use crate::app::config;
+use crate::app::errors::{ErrorResponse, ServiceError};
+use crate::app::security::random_request_id;
+
+/// header carrying the request id on requests and responses
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// true for client-supplied request ids safe to echo back and log
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty() && request_id.len() <= config::TOKEN_LENGTH &&
+        request_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// middleware assigning each request an id, reusing a valid incoming `X-Request-Id`,
+/// and rendering error responses as the ErrorResponse envelope carrying that id
+pub fn assign_request_id<S>(req: ServiceRequest, srv: &mut S)
+    -> impl Future<Output = Result<ServiceResponse<Body>, Error>>
+    where S: Service<Request = ServiceRequest, Response = ServiceResponse<Body>, Error = Error>
+{
+    let request_id = req.headers().get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(|value| value.to_string())
+        .unwrap_or_else(random_request_id);
+
+    let fut = srv.call(req);
+
+    async move {
+        let mut res = fut.await?;
+
+        let error_response = res.response().error().map(|error| {
+            let status = res.status();
+            if status.is_server_error() {
+                log::error!("request {} failed: {}", request_id, error);
+            } else {
+                log::debug!("request {} failed: {}", request_id, error);
+            }
+
+            match error.as_error::<ServiceError>() {
+                Some(service_error) => service_error.to_response(Some(request_id.clone())),
+                // errors from actix itself, e.g. malformed json or query strings
+                None => HttpResponse::build(status).json(
+                    ErrorResponse::from_status(status, error.to_string(), Some(request_id.clone()))),
+            }
+        });
+        if let Some(mut error_response) = error_response {
+            // keep headers set along with the error, e.g. Retry-After or WWW-Authenticate
+            let envelope_headers = error_response.headers().keys().cloned().collect::<Vec<_>>();
+            for (name, value) in res.headers().iter() {
+                if !envelope_headers.contains(name) && name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                    error_response.headers_mut().append(name.clone(), value.clone());
+                }
+            }
+            res = res.into_response(error_response);
+        }
+
+        res.headers_mut().insert(
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            HeaderValue::from_str(&request_id).unwrap());
+        Ok(res)
+    }
+}
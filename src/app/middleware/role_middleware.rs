@@ -0,0 +1,71 @@
+use std::ops::Deref;
+use std::pin::Pin;
+use actix_web::{dev::Payload, Error, FromRequest, HttpRequest};
+use futures::future::Future;
+
+use crate::app::errors::ServiceError;
+use crate::app::middleware::auth_middleware::SessionUser;
+use crate::app::models::Role;
+
+/// resolves the session user, rejecting users without at least the required role
+fn session_user_with_role(req: &HttpRequest, pl: &mut Payload, required_role: Role)
+    -> impl Future<Output = Result<SessionUser, Error>>
+{
+    let fut = SessionUser::from_request(req, pl);
+
+    async move {
+        let session_user = fut.await?;
+        if session_user.role < required_role {
+            return Err(ServiceError::Forbidden.into());
+        }
+        Ok(session_user)
+    }
+}
+
+/// SessionUser with at least the editor role, for changing dictionary data
+#[derive(Debug)]
+pub struct EditorUser(pub SessionUser);
+
+impl Deref for EditorUser {
+    type Target = SessionUser;
+
+    fn deref(&self) -> &SessionUser {
+        &self.0
+    }
+}
+
+/// middleware for requiring the editor role
+impl FromRequest for EditorUser {
+    type Config = ();
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<EditorUser, Error>>>>;
+
+    fn from_request(req: &HttpRequest, pl: &mut Payload) -> Self::Future {
+        let fut = session_user_with_role(req, pl, Role::Editor);
+        Box::pin(async move { Ok(EditorUser(fut.await?)) })
+    }
+}
+
+/// SessionUser with the admin role, for managing users
+#[derive(Debug)]
+pub struct AdminUser(pub SessionUser);
+
+impl Deref for AdminUser {
+    type Target = SessionUser;
+
+    fn deref(&self) -> &SessionUser {
+        &self.0
+    }
+}
+
+/// middleware for requiring the admin role
+impl FromRequest for AdminUser {
+    type Config = ();
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<AdminUser, Error>>>>;
+
+    fn from_request(req: &HttpRequest, pl: &mut Payload) -> Self::Future {
+        let fut = session_user_with_role(req, pl, Role::Admin);
+        Box::pin(async move { Ok(AdminUser(fut.await?)) })
+    }
+}
@@ -7,4 +7,7 @@ pub mod identity;
 pub mod middleware;
 pub mod models;
 pub mod routes;
+pub mod search;
 pub mod security;
+pub mod tags;
+pub mod validation;
@@ -0,0 +1,29 @@
+use chrono::{DateTime,Utc};
+use serde::{Deserialize,Serialize};
+
+use crate::schema::*;
+use super::user::User;
+
+/// ApiToken records, personal access tokens stored only as a hash
+#[derive(Debug, Serialize, Deserialize, Queryable, Associations, PartialEq)]
+#[belongs_to(User)]
+#[table_name = "api_tokens"]
+pub struct ApiToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    #[serde(skip)]
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// NewApiToken struct for fields necessary when inserting a new api token record
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[table_name = "api_tokens"]
+pub struct NewApiToken {
+    pub user_id: i32,
+    pub name: String,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+}
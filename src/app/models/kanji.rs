@@ -0,0 +1,37 @@
+use serde::{Deserialize,Serialize};
+
+use crate::schema::kanji;
+
+/// Kanji records, a character of the KANJIDIC2 dictionary
+#[derive(Debug, Clone, Deserialize, Serialize, Identifiable, Queryable)]
+#[table_name = "kanji"]
+pub struct Kanji {
+    pub id: i32,
+    pub literal: String,
+    /// accepted stroke count
+    pub stroke_count: Option<i32>,
+    /// school grade the kanji is taught in, 1 to 6 for kyouiku kanji, 8 for other jouyou kanji
+    /// and 9 or 10 for jinmeiyou kanji
+    pub grade: Option<i32>,
+    /// level of the former four level JLPT, 1 being the most advanced
+    pub jlpt: Option<i32>,
+    /// rank among the 2500 most frequent kanji in newspapers
+    pub frequency: Option<i32>,
+    /// classical (KangXi) radical number
+    pub radical: Option<i32>,
+    /// radical number of the Nelson dictionary, where it differs from the classical radical
+    pub nelson_radical: Option<i32>,
+}
+
+/// NewKanji struct for inserting a new kanji record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Insertable)]
+#[table_name = "kanji"]
+pub struct NewKanji {
+    pub literal: String,
+    pub stroke_count: Option<i32>,
+    pub grade: Option<i32>,
+    pub jlpt: Option<i32>,
+    pub frequency: Option<i32>,
+    pub radical: Option<i32>,
+    pub nelson_radical: Option<i32>,
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize,Serialize};
+
+use crate::schema::{kanji,kanji_meanings};
+use crate::app::models::kanji::{Kanji};
+
+/// KanjiMeaning records
+#[derive(Associations, Debug, Clone, Serialize, Identifiable, Deserialize, Queryable)]
+#[belongs_to(Kanji, foreign_key = "kanji_id")]
+pub struct KanjiMeaning {
+    pub id: i32,
+    pub kanji_id: i32,
+    pub meaning: String,
+    pub meaning_lang: String,
+}
+
+/// NewKanjiMeaning struct for inserting a new kanji_meanings record
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[table_name = "kanji_meanings"]
+pub struct NewKanjiMeaning {
+    pub kanji_id: i32,
+    pub meaning: String,
+    pub meaning_lang: String,
+}
+
+joinable!(kanji_meanings -> kanji(kanji_id));
@@ -0,0 +1,26 @@
+use serde::{Deserialize,Serialize};
+
+use crate::schema::{kanji,kanji_readings};
+use crate::app::models::kanji::{Kanji};
+
+/// KanjiReading records
+#[derive(Associations, Debug, Clone, Serialize, Identifiable, Deserialize, Queryable)]
+#[belongs_to(Kanji, foreign_key = "kanji_id")]
+pub struct KanjiReading {
+    pub id: i32,
+    pub kanji_id: i32,
+    pub reading: String,
+    /// `ja_on` for on readings, `ja_kun` for kun readings and `nanori` for name readings
+    pub reading_type: String,
+}
+
+/// NewKanjiReading struct for inserting a new kanji_readings record
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[table_name = "kanji_readings"]
+pub struct NewKanjiReading {
+    pub kanji_id: i32,
+    pub reading: String,
+    pub reading_type: String,
+}
+
+joinable!(kanji_readings -> kanji(kanji_id));
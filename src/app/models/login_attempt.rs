@@ -0,0 +1,24 @@
+use chrono::{DateTime,Utc};
+use serde::{Deserialize,Serialize};
+
+use crate::schema::*;
+
+/// LoginAttempt records, kept for throttling logins and reviewing failed attempts
+#[derive(Debug, Serialize, Deserialize, Queryable, PartialEq)]
+pub struct LoginAttempt {
+    pub id: i32,
+    pub user_name: String,
+    pub client_ip: Option<String>,
+    pub succeeded: bool,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// NewLoginAttempt struct for fields necessary when inserting a new login attempt record
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[table_name = "login_attempts"]
+pub struct NewLoginAttempt {
+    pub user_name: String,
+    pub client_ip: Option<String>,
+    pub succeeded: bool,
+    pub attempted_at: DateTime<Utc>,
+}
@@ -1,3 +1,15 @@
+pub mod api_token;
+pub use api_token::*;
+pub mod kanji;
+pub use kanji::*;
+pub mod kanji_meaning;
+pub use kanji_meaning::*;
+pub mod kanji_reading;
+pub use kanji_reading::*;
+pub mod login_attempt;
+pub use login_attempt::*;
+pub mod password_reset;
+pub use password_reset::*;
 pub mod session;
 pub use session::*;
 pub mod source;
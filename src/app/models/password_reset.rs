@@ -0,0 +1,37 @@
+use chrono::{DateTime,Utc};
+use serde::{Deserialize,Serialize};
+
+use crate::schema::*;
+use crate::app::config;
+use crate::app::security::hash_token;
+use super::user::User;
+
+/// PasswordReset records, single-use tokens for setting a new password stored only as a hash
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Associations, PartialEq)]
+#[belongs_to(User)]
+#[table_name = "password_resets"]
+pub struct PasswordReset {
+    #[serde(skip)]
+    pub token_hash: String,
+    pub user_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PasswordReset {
+    /// constructor method generates new PasswordReset record objects for a random token
+    pub fn create<S: Into<i32>>(user_id: S, token: &str) -> Self {
+        let now = Utc::now();
+        PasswordReset {
+            token_hash: hash_token(token),
+            user_id: user_id.into(),
+            created_at: now,
+            expires_at: now + *config::PASSWORD_RESET_EXPIRY,
+        }
+    }
+
+    /// true if the reset token can no longer be used
+    pub fn is_expired(&self) -> bool {
+        self.expires_at < Utc::now()
+    }
+}
@@ -2,6 +2,7 @@ use chrono::{DateTime,Utc};
 use serde::{Deserialize,Serialize};
 
 use crate::schema::*;
+use crate::app::config;
 use crate::app::security::random_token;
 use super::user::User;
 
@@ -28,4 +29,11 @@ impl Session {
             accessed_by_client_ip: None,
         }
     }
+
+    /// true if the session has been idle too long or is past its absolute lifetime
+    pub fn is_expired(&self) -> bool {
+        let now = Utc::now();
+        self.last_accessed_at + *config::SESSION_IDLE_TIMEOUT < now ||
+            self.created_at + *config::SESSION_MAX_AGE < now
+    }
 }
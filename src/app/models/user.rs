@@ -1,8 +1,43 @@
 use chrono::{DateTime,Utc};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 use crate::schema::*;
 
+/// Role of a user, ordered so that each role includes the permissions of the ones before it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Reader,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    /// name of the role as stored in users.role
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Reader => "reader",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    /// parses a role name as stored in users.role
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        match role {
+            "reader" => Ok(Role::Reader),
+            "editor" => Ok(Role::Editor),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("unknown role {:?}", role)),
+        }
+    }
+}
+
 /// User record with all fields
 #[derive(Debug, Serialize, Deserialize, Queryable)]
 pub struct User {
@@ -10,6 +45,7 @@ pub struct User {
     pub name: String,
     pub passhash: String,
     pub created_at: DateTime<Utc>,
+    pub role: String,
 }
 
 /// SlimUser user record with only session-pertinent fields
@@ -17,12 +53,14 @@ pub struct User {
 pub struct SlimUser {
     pub id: i32,
     pub name: String,
+    pub role: Role,
 }
 
 impl From<User> for SlimUser {
-    /// picks pertinent fields from User record
+    /// picks pertinent fields from User record, unknown roles get no extra permissions
     fn from(user: User) -> Self {
-        SlimUser { id: user.id, name: user.name }
+        let role = user.role.parse().unwrap_or(Role::Reader);
+        SlimUser { id: user.id, name: user.name, role }
     }
 }
 
@@ -26,3 +26,23 @@ pub struct NewWordEntry {
     pub sense: i32,
     pub group_id: i32
 }
+
+/// WordEntryChangeset struct for partially updating a word_entries record
+#[derive(Debug, Clone, Default, Serialize, Deserialize, AsChangeset)]
+#[table_name = "word_entries"]
+pub struct WordEntryChangeset {
+    pub orth: Option<String>,
+    pub orth_lang: Option<String>,
+    pub quote: Option<String>,
+    pub quote_lang: Option<String>,
+    pub sense: Option<i32>,
+    pub group_id: Option<i32>,
+}
+
+impl WordEntryChangeset {
+    /// true if no fields would be updated by this changeset
+    pub fn is_empty(&self) -> bool {
+        self.orth.is_none() && self.orth_lang.is_none() && self.quote.is_none() &&
+            self.quote_lang.is_none() && self.sense.is_none() && self.group_id.is_none()
+    }
+}
@@ -8,11 +8,25 @@ use crate::schema::word_entry_groups;
 pub struct WordEntryGroup {
     pub id: i32,
     pub source_id: i32,
+    /// stable key of the entry in the source dictionary, e.g. an edict2 EntL sequence id
+    pub upstream_key: Option<String>,
+    /// digest of the imported upstream entry, for detecting modified entries on updates
+    #[serde(skip)]
+    pub upstream_hash: Option<String>,
 }
 
 /// NewWordEntryGroup struct for inserting a new word_entry_groups record
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
 #[table_name = "word_entry_groups"]
 pub struct NewWordEntryGroup {
-    pub source_id: i32
+    pub source_id: i32,
+    pub upstream_key: Option<String>,
+    pub upstream_hash: Option<String>,
+}
+
+impl NewWordEntryGroup {
+    /// group without an upstream key, replaced as a whole on re-imports
+    pub fn from_source(source_id: i32) -> Self {
+        NewWordEntryGroup { source_id, upstream_key: None, upstream_hash: None }
+    }
 }
@@ -11,6 +11,7 @@ pub struct WordEntryReading {
     pub word_entry_id: i32,
     pub reading: String,
     pub reading_tag: Option<String>,
+    pub search_key: Option<String>,
 }
 
 /// NewWordEntryReading struct for inserting a new word_entry_readings record
@@ -20,6 +21,7 @@ pub struct NewWordEntryReading {
     pub word_entry_id: i32,
     pub reading: String,
     pub reading_tag: Option<String>,
+    pub search_key: Option<String>,
 }
 
 joinable!(word_entry_readings -> word_entries(word_entry_id));
@@ -6,15 +6,78 @@ use crate::app::controllers::*;
 pub fn build_routes(cfg: &mut web::ServiceConfig) {
     cfg
         // routes
+        .service(
+            web::resource("/api_tokens")
+                .route(web::get().to(api_tokens_controller::list_api_tokens))
+                .route(web::post().to(api_tokens_controller::create_api_token))
+        )
+        .service(
+            web::resource("/api_tokens/{id}")
+                .route(web::delete().to(api_tokens_controller::delete_api_token))
+        )
         .service(
             web::resource("/auth")
                 .route(web::post().to(auth_controller::login))
                 .route(web::delete().to(auth_controller::logout))
                 .route(web::get().to(auth_controller::get_me))
         )
+        .service(
+            web::resource("/auth/password")
+                .route(web::put().to(auth_controller::change_password))
+        )
+        .service(
+            web::resource("/auth/password_reset")
+                .route(web::post().to(auth_controller::reset_password))
+        )
+        .service(
+            web::resource("/kanji/{char}")
+                .route(web::get().to(kanji_controller::get_kanji))
+        )
+        .service(
+            web::resource("/login_attempts")
+                .route(web::get().to(login_attempts_controller::list_failed_login_attempts))
+        )
+        .service(
+            web::resource("/sessions")
+                .route(web::get().to(sessions_controller::list_sessions))
+        )
+        .service(
+            web::resource("/sessions/{id}")
+                .route(web::delete().to(sessions_controller::delete_session))
+        )
+        .service(
+            web::resource("/tags")
+                .route(web::get().to(tags_controller::list_tags))
+        )
+        .service(
+            web::resource("/users")
+                .route(web::post().to(users_controller::create_user))
+        )
+        .service(
+            web::resource("/users/{id}/role")
+                .route(web::put().to(users_controller::update_user_role))
+        )
+        .service(
+            web::resource("/users/{id}/password_reset")
+                .route(web::post().to(users_controller::create_password_reset))
+        )
         .service(
             web::resource("/word_entries")
                 .route(web::get().to(word_entries_controller::list_word_entries))
-                // .route(web::post().to(word_entries_controller::create_word_entry))
+                .route(web::post().to(word_entries_controller::create_word_entry))
+        )
+        .service(
+            web::resource("/word_entries/{id}")
+                .route(web::get().to(word_entries_controller::get_word_entry))
+                .route(web::patch().to(word_entries_controller::update_word_entry))
+                .route(web::delete().to(word_entries_controller::delete_word_entry))
+        )
+        .service(
+            web::resource("/word_entry_groups")
+                .route(web::get().to(word_entry_groups_controller::list_word_entry_groups))
+        )
+        .service(
+            web::resource("/word_entry_groups/{id}")
+                .route(web::get().to(word_entry_groups_controller::get_word_entry_group))
         );
 }
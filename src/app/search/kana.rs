@@ -0,0 +1,294 @@
+//! Kana and Hepburn romaji transliteration
+use std::collections::HashMap;
+
+/// hiragana to Hepburn romaji, digraphs before their single kana
+const KANA_ROMAJI: &[(&str, &str)] = &[
+    // digraphs
+    ("きゃ", "kya"), ("きゅ", "kyu"), ("きょ", "kyo"), ("しゃ", "sha"), ("しゅ", "shu"), ("しょ", "sho"),
+    ("ちゃ", "cha"), ("ちゅ", "chu"), ("ちょ", "cho"), ("にゃ", "nya"), ("にゅ", "nyu"), ("にょ", "nyo"),
+    ("ひゃ", "hya"), ("ひゅ", "hyu"), ("ひょ", "hyo"), ("みゃ", "mya"), ("みゅ", "myu"), ("みょ", "myo"),
+    ("りゃ", "rya"), ("りゅ", "ryu"), ("りょ", "ryo"), ("ぎゃ", "gya"), ("ぎゅ", "gyu"), ("ぎょ", "gyo"),
+    ("じゃ", "ja"), ("じゅ", "ju"), ("じょ", "jo"), ("ぢゃ", "ja"), ("ぢゅ", "ju"), ("ぢょ", "jo"),
+    ("びゃ", "bya"), ("びゅ", "byu"), ("びょ", "byo"), ("ぴゃ", "pya"), ("ぴゅ", "pyu"), ("ぴょ", "pyo"),
+    // extended digraphs, mostly from katakana loanwords
+    ("ふぁ", "fa"), ("ふぃ", "fi"), ("ふぇ", "fe"), ("ふぉ", "fo"), ("てぃ", "ti"), ("でぃ", "di"),
+    ("とぅ", "tu"), ("どぅ", "du"), ("でゅ", "dyu"), ("うぃ", "wi"), ("うぇ", "we"), ("うぉ", "wo"),
+    ("しぇ", "she"), ("じぇ", "je"), ("ちぇ", "che"), ("つぁ", "tsa"), ("つぃ", "tsi"), ("つぇ", "tse"),
+    ("つぉ", "tso"), ("ゔぁ", "va"), ("ゔぃ", "vi"), ("ゔぇ", "ve"), ("ゔぉ", "vo"), ("いぇ", "ye"),
+    // single kana
+    ("あ", "a"), ("い", "i"), ("う", "u"), ("え", "e"), ("お", "o"),
+    ("か", "ka"), ("き", "ki"), ("く", "ku"), ("け", "ke"), ("こ", "ko"),
+    ("さ", "sa"), ("し", "shi"), ("す", "su"), ("せ", "se"), ("そ", "so"),
+    ("た", "ta"), ("ち", "chi"), ("つ", "tsu"), ("て", "te"), ("と", "to"),
+    ("な", "na"), ("に", "ni"), ("ぬ", "nu"), ("ね", "ne"), ("の", "no"),
+    ("は", "ha"), ("ひ", "hi"), ("ふ", "fu"), ("へ", "he"), ("ほ", "ho"),
+    ("ま", "ma"), ("み", "mi"), ("む", "mu"), ("め", "me"), ("も", "mo"),
+    ("や", "ya"), ("ゆ", "yu"), ("よ", "yo"),
+    ("ら", "ra"), ("り", "ri"), ("る", "ru"), ("れ", "re"), ("ろ", "ro"),
+    ("わ", "wa"), ("ゐ", "i"), ("ゑ", "e"), ("を", "o"), ("ん", "n"),
+    ("が", "ga"), ("ぎ", "gi"), ("ぐ", "gu"), ("げ", "ge"), ("ご", "go"),
+    ("ざ", "za"), ("じ", "ji"), ("ず", "zu"), ("ぜ", "ze"), ("ぞ", "zo"),
+    ("だ", "da"), ("ぢ", "ji"), ("づ", "zu"), ("で", "de"), ("ど", "do"),
+    ("ば", "ba"), ("び", "bi"), ("ぶ", "bu"), ("べ", "be"), ("ぼ", "bo"),
+    ("ぱ", "pa"), ("ぴ", "pi"), ("ぷ", "pu"), ("ぺ", "pe"), ("ぽ", "po"),
+    ("ゔ", "vu"),
+    // small kana on their own
+    ("ぁ", "a"), ("ぃ", "i"), ("ぅ", "u"), ("ぇ", "e"), ("ぉ", "o"),
+    ("ゃ", "ya"), ("ゅ", "yu"), ("ょ", "yo"), ("ゎ", "wa"),
+];
+
+/// romaji spellings only used for romaji to kana, e.g. Nihon-shiki input
+const ROMAJI_ALIASES: &[(&str, &str)] = &[
+    ("si", "し"), ("zi", "じ"), ("hu", "ふ"), ("wo", "を"),
+    ("sya", "しゃ"), ("syu", "しゅ"), ("syo", "しょ"), ("tya", "ちゃ"), ("tyu", "ちゅ"), ("tyo", "ちょ"),
+    ("zya", "じゃ"), ("zyu", "じゅ"), ("zyo", "じょ"), ("jya", "じゃ"), ("jyu", "じゅ"), ("jyo", "じょ"),
+];
+
+/// sokuon (small tsu) and long vowel mark
+const SOKUON: char = 'っ';
+const CHOONPU: char = 'ー';
+
+/// offset between katakana and hiragana code points
+const KATAKANA_OFFSET: u32 = 0x60;
+
+lazy_static::lazy_static! {
+    // kana to romaji lookup
+    static ref KANA_TO_ROMAJI: HashMap<&'static str, &'static str> = KANA_ROMAJI.iter().cloned().collect();
+    // romaji to kana lookup, the first kana listed for a romaji spelling wins,
+    // small kana and obsolete kana are never produced
+    static ref ROMAJI_TO_KANA: HashMap<&'static str, &'static str> = {
+        let mut map: HashMap<&'static str, &'static str> = ROMAJI_ALIASES.iter().cloned().collect();
+        for (kana, romaji) in KANA_ROMAJI.iter() {
+            if !["ぁ", "ぃ", "ぅ", "ぇ", "ぉ", "ゃ", "ゅ", "ょ", "ゎ", "ゐ", "ゑ", "を"].contains(kana) {
+                map.entry(romaji).or_insert(kana);
+            }
+        }
+        map
+    };
+}
+
+/// converts a katakana char to hiragana, other chars are unchanged
+fn katakana_to_hiragana_char(c: char) -> char {
+    match c as u32 {
+        0x30A1..=0x30F6 => std::char::from_u32(c as u32 - KATAKANA_OFFSET).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// converts a hiragana char to katakana, other chars are unchanged
+fn hiragana_to_katakana_char(c: char) -> char {
+    match c as u32 {
+        0x3041..=0x3096 => std::char::from_u32(c as u32 + KATAKANA_OFFSET).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// true for hiragana and katakana chars, including the long vowel mark
+pub fn is_kana(c: char) -> bool {
+    let code = c as u32;
+    (0x3041..=0x3096).contains(&code) || (0x30A1..=0x30FA).contains(&code) || code == 0x30FC
+}
+
+/// true if text contains any kana
+pub fn contains_kana(text: &str) -> bool {
+    text.chars().any(is_kana)
+}
+
+/// converts hiragana and katakana to Hepburn romaji, with long vowels written
+/// twice and `n'` before vowels and y, other chars are unchanged
+pub fn to_romaji(text: &str) -> String {
+    let chars: Vec<char> = text.chars().map(katakana_to_hiragana_char).collect();
+    let mut romaji = String::new();
+    let mut double_next = false;
+    let mut idx = 0;
+    while idx < chars.len() {
+        let c = chars[idx];
+        if c == SOKUON {
+            double_next = true;
+            idx += 1;
+            continue;
+        }
+        if c == CHOONPU {
+            // repeat the previous vowel
+            if let Some(vowel) = romaji.chars().last().filter(|v| "aiueo".contains(*v)) {
+                romaji.push(vowel);
+            }
+            idx += 1;
+            continue;
+        }
+
+        // try digraphs first, then single kana
+        let digraph: String = chars[idx..(idx + 2).min(chars.len())].iter().collect();
+        let single = c.to_string();
+        let (matched, len) = if let Some(r) = KANA_TO_ROMAJI.get(digraph.as_str()).filter(|_| digraph.chars().count() == 2) {
+            (Some(*r), 2)
+        } else {
+            (KANA_TO_ROMAJI.get(single.as_str()).copied(), 1)
+        };
+
+        match matched {
+            Some(r) => {
+                if double_next {
+                    // sokuon doubles the next consonant, ch becomes tch
+                    if r.starts_with("ch") {
+                        romaji.push('t');
+                    } else if let Some(first) = r.chars().next().filter(|f| !"aiueo".contains(*f)) {
+                        romaji.push(first);
+                    }
+                }
+                // separate syllabic n from a following vowel or y
+                if romaji.ends_with('n') && chars[..idx].last() == Some(&'ん') &&
+                    r.starts_with(|f: char| "aiueoy".contains(f))
+                {
+                    romaji.push('\'');
+                }
+                romaji.push_str(r);
+            }
+            None => romaji.extend(chars[idx..idx + len].iter()),
+        }
+        double_next = false;
+        idx += len;
+    }
+    romaji
+}
+
+/// converts Hepburn (or Nihon-shiki) romaji to hiragana, unconvertible chars are unchanged
+pub fn to_hiragana(text: &str) -> String {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    // Option::is_some_and needs a newer rust than the supported 1.41
+    #[allow(clippy::unnecessary_map_or)]
+    let is_vowel = |c: Option<&char>| c.map_or(false, |c| "aiueo".contains(*c));
+    let mut kana = String::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let c = chars[idx];
+        let next = chars.get(idx + 1);
+
+        if c == '-' {
+            kana.push(CHOONPU);
+            idx += 1;
+        } else if c == '\'' {
+            idx += 1;
+        } else if c == 'n' && next == Some(&'n') && !is_vowel(chars.get(idx + 2)) {
+            // nn before a consonant or at the end
+            kana.push('ん');
+            idx += 2;
+        } else if c == 'n' && !is_vowel(next) && next != Some(&'y') {
+            kana.push('ん');
+            idx += 1;
+        } else if c.is_ascii_alphabetic() && !is_vowel(Some(&c)) &&
+            (next == Some(&c) || (c == 't' && next == Some(&'c')))
+        {
+            // doubled consonant, or tch
+            kana.push(SOKUON);
+            idx += 1;
+        } else {
+            // longest romaji match
+            let matched = (1..=3).rev().filter(|len| idx + len <= chars.len()).find_map(|len| {
+                let candidate: String = chars[idx..idx + len].iter().collect();
+                ROMAJI_TO_KANA.get(candidate.as_str()).map(|k| (*k, len))
+            });
+            match matched {
+                Some((k, len)) => {
+                    kana.push_str(k);
+                    idx += len;
+                }
+                None => {
+                    kana.push(c);
+                    idx += 1;
+                }
+            }
+        }
+    }
+    kana
+}
+
+/// converts romaji or hiragana to katakana
+pub fn to_katakana(text: &str) -> String {
+    to_hiragana(text).chars().map(hiragana_to_katakana_char).collect()
+}
+
+/// normalized romaji search key for kana or romaji text
+///
+/// macrons and long vowel marks are spelled out as doubled vowels, with a long o as `ou`,
+/// so `とうきょう`, `トーキョー`, `toukyou` and `tōkyō` all become `toukyou`
+///
+/// vowels are never shortened, since kana can't tell a long vowel from a vowel starting
+/// the next morpheme, e.g. `おもう` and `おも` or `おじいさん` and `おじさん` keep distinct keys
+pub fn romaji_key(text: &str) -> String {
+    let mut key = String::new();
+    for c in to_romaji(text).to_lowercase().chars() {
+        match c {
+            'ā' | 'â' => key.push_str("aa"),
+            'ī' | 'î' => key.push_str("ii"),
+            'ū' | 'û' => key.push_str("uu"),
+            'ē' | 'ê' => key.push_str("ee"),
+            'ō' | 'ô' => key.push_str("ou"),
+            c if c.is_ascii_lowercase() => key.push(c),
+            _ => (),
+        }
+    }
+
+    // spell long o and m before labials the same way throughout
+    for (spelling, normalized) in [("oo", "ou"), ("mb", "nb"), ("mp", "np")].iter() {
+        key = key.replace(spelling, normalized);
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_romaji() {
+        assert_eq!(to_romaji("たべる"), "taberu");
+        assert_eq!(to_romaji("がっこう"), "gakkou");
+        assert_eq!(to_romaji("まっちゃ"), "matcha");
+        assert_eq!(to_romaji("しんよう"), "shin'you");
+        assert_eq!(to_romaji("ラーメン"), "raamen");
+        assert_eq!(to_romaji("コンピューター"), "konpyuutaa");
+        assert_eq!(to_romaji("ファイル"), "fairu");
+        assert_eq!(to_romaji("食べる"), "食beru");
+    }
+
+    #[test]
+    fn test_to_hiragana() {
+        assert_eq!(to_hiragana("taberu"), "たべる");
+        assert_eq!(to_hiragana("gakkou"), "がっこう");
+        assert_eq!(to_hiragana("matcha"), "まっちゃ");
+        assert_eq!(to_hiragana("kon'nichiha"), "こんにちは");
+        assert_eq!(to_hiragana("shinbun"), "しんぶん");
+        assert_eq!(to_hiragana("onna"), "おんな");
+        assert_eq!(to_hiragana("hon"), "ほん");
+        assert_eq!(to_hiragana("sinbun"), "しんぶん");
+    }
+
+    #[test]
+    fn test_to_katakana() {
+        assert_eq!(to_katakana("ra-men"), "ラーメン");
+        assert_eq!(to_katakana("たべる"), "タベル");
+    }
+
+    #[test]
+    fn test_romaji_key() {
+        assert_eq!(romaji_key("とうきょう"), "toukyou");
+        assert_eq!(romaji_key("トーキョー"), "toukyou");
+        assert_eq!(romaji_key("toukyou"), "toukyou");
+        assert_eq!(romaji_key("tōkyō"), "toukyou");
+        assert_eq!(romaji_key("Tokyo"), "tokyo");
+        assert_eq!(romaji_key("おおきい"), romaji_key("ōkii"));
+        assert_eq!(romaji_key("らーめん"), romaji_key("rāmen"));
+        assert_eq!(romaji_key("しんぶん"), "shinbun");
+        assert_eq!(romaji_key("shimbun"), "shinbun");
+        assert_eq!(romaji_key("たべる"), romaji_key("taberu"));
+    }
+
+    #[test]
+    fn test_romaji_key_keeps_vowel_length() {
+        assert_ne!(romaji_key("おもう"), romaji_key("おも"));
+        assert_ne!(romaji_key("おじいさん"), romaji_key("おじさん"));
+        assert_ne!(romaji_key("おばあさん"), romaji_key("おばさん"));
+        assert_ne!(romaji_key("ゆうき"), romaji_key("ゆき"));
+    }
+}
@@ -0,0 +1,260 @@
+//! Search modes and matching expressions for word entry lookups
+pub mod kana;
+pub mod pinyin;
+
+use diesel::dsl::sql;
+use diesel::expression::BoxableExpression;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Float, Nullable, Text};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{word_entries,word_entry_notes,word_entry_readings};
+
+/// boxed boolean expression over word_entries, for building filters
+pub type WordEntriesPredicate = Box<dyn BoxableExpression<word_entries::table, Pg, SqlType = Bool>>;
+/// boxed boolean expression over word_entry_readings
+pub type WordEntryReadingsPredicate = Box<dyn BoxableExpression<word_entry_readings::table, Pg, SqlType = Bool>>;
+/// boxed relevance score expression over word_entries
+pub type WordEntriesScore = Box<dyn BoxableExpression<word_entries::table, Pg, SqlType = Nullable<Float>>>;
+
+sql_function! {
+    /// best relevance score among grouped rows
+    #[sql_name = "max"]
+    fn max_score(score: Nullable<Float>) -> Nullable<Float>;
+}
+
+/// text search strategy for GET /word_entries
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// case-insensitive LIKE pattern on orth or quote, unranked
+    Like,
+    /// pg_trgm similarity on orth or quote, ranked by similarity
+    Fuzzy,
+    /// full text match on the words of orth and quote, ranked by ts_rank
+    Fulltext,
+    /// case-insensitive LIKE pattern on word_entry_readings, unranked
+    Reading,
+}
+
+// #[default] on enum variants needs a newer rust than the supported 1.41
+#[allow(clippy::derivable_impls)]
+impl Default for SearchMode {
+    fn default() -> Self {
+        SearchMode::Like
+    }
+}
+
+impl SearchMode {
+    /// true if results of this mode carry a relevance score
+    pub fn is_ranked(self) -> bool {
+        self == SearchMode::Fuzzy || self == SearchMode::Fulltext
+    }
+
+    /// true if this mode matches with LIKE patterns, and so supports a MatchMode
+    pub fn is_like(self) -> bool {
+        self == SearchMode::Like || self == SearchMode::Reading
+    }
+}
+
+/// how a literal query matches text in LIKE based search modes
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    Exact,
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+impl MatchMode {
+    /// LIKE pattern matching the query literally in this mode
+    pub fn like_pattern(self, query: &str) -> String {
+        let escaped = escape_like(query);
+        match self {
+            MatchMode::Exact => escaped,
+            MatchMode::Prefix => format!("{}%", escaped),
+            MatchMode::Suffix => format!("%{}", escaped),
+            MatchMode::Contains => format!("%{}%", escaped),
+        }
+    }
+}
+
+/// word entry text a LIKE search is restricted to
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchField {
+    Orth,
+    Quote,
+    Reading,
+    Note,
+}
+
+/// escapes LIKE metacharacters with postgres' default escape character
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\\' || c == '%' || c == '_' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// LIKE pattern for a query, which is taken as a raw pattern without a match mode
+pub fn like_pattern(match_mode: Option<MatchMode>, query: &str) -> String {
+    match match_mode {
+        Some(match_mode) => match_mode.like_pattern(query),
+        None => query.to_string(),
+    }
+}
+
+/// normalized search key stored with a reading, for orth languages with
+/// alternate spellings of readings
+pub fn reading_search_key(orth_lang: &str, reading: &str) -> Option<String> {
+    let key = match orth_lang {
+        "zho" => pinyin::search_key(reading),
+        "jpn" => kana::romaji_key(reading),
+        _ => return None,
+    };
+    if key.is_empty() { None } else { Some(key) }
+}
+
+/// candidate reading search keys for a query typed in any supported spelling
+pub fn query_search_keys(query: &str) -> Vec<String> {
+    let mut keys = vec![pinyin::search_key(query), kana::romaji_key(query)];
+    keys.retain(|key| !key.is_empty());
+    keys.dedup();
+    keys
+}
+
+/// exact reading spellings for a query, tone-number pinyin as stored by cedict
+/// and kana as stored by edict2
+pub fn query_reading_spellings(query: &str) -> Vec<String> {
+    let mut spellings = vec![pinyin::to_tone_numbers(query)];
+    if !kana::contains_kana(query) {
+        spellings.push(kana::to_hiragana(query));
+        spellings.push(kana::to_katakana(query));
+    }
+    spellings.dedup();
+    spellings
+}
+
+/// expression matching word_entry_readings for a reading query, also used
+/// to pick out which readings of a result matched
+///
+/// with a match mode, alternate spellings and search keys are matched in that mode as well
+pub fn reading_match_expression(query: &str, match_mode: Option<MatchMode>) -> WordEntryReadingsPredicate {
+    let match_mode = match match_mode {
+        Some(match_mode) => match_mode,
+        None => return Box::new(
+            word_entry_readings::reading.ilike(query.to_string())
+                .or(word_entry_readings::reading.eq_any(query_reading_spellings(query)))
+                .or(word_entry_readings::search_key.eq_any(query_search_keys(query)))
+        ),
+    };
+
+    let mut predicate: WordEntryReadingsPredicate =
+        Box::new(word_entry_readings::reading.ilike(match_mode.like_pattern(query)));
+    for spelling in query_reading_spellings(query) {
+        predicate = Box::new(predicate.or(word_entry_readings::reading.like(match_mode.like_pattern(&spelling))));
+    }
+    for key in query_search_keys(query) {
+        predicate = Box::new(predicate.or(word_entry_readings::search_key.like(match_mode.like_pattern(&key))));
+    }
+    predicate
+}
+
+/// expression picking out the readings matched by a search, None if it does not search readings
+pub fn matched_readings_expression(
+    mode: SearchMode,
+    query: &str,
+    match_mode: Option<MatchMode>,
+    field: Option<SearchField>,
+) -> Option<WordEntryReadingsPredicate> {
+    match (mode, field) {
+        (SearchMode::Reading, _) => Some(reading_match_expression(query, match_mode)),
+        (SearchMode::Like, Some(SearchField::Reading)) =>
+            Some(Box::new(word_entry_readings::reading.ilike(like_pattern(match_mode, query)))),
+        _ => None,
+    }
+}
+
+/// expression matching word entries with a reading matching the predicate
+fn readings_subselect(predicate: WordEntryReadingsPredicate) -> WordEntriesPredicate {
+    Box::new(
+        word_entries::id.eq_any(
+            word_entry_readings::table
+                .select(word_entry_readings::word_entry_id)
+                .filter(predicate)
+                .into_boxed()
+        )
+    )
+}
+
+/// expression matching word entries by LIKE pattern, on orth or quote unless restricted to a field
+fn like_expression(field: Option<SearchField>, pattern: String) -> WordEntriesPredicate {
+    match field {
+        None => Box::new(
+            word_entries::orth.ilike(pattern.clone())
+                .or(word_entries::quote.ilike(pattern))
+        ),
+        Some(SearchField::Orth) => Box::new(word_entries::orth.ilike(pattern)),
+        Some(SearchField::Quote) => Box::new(word_entries::quote.ilike(pattern)),
+        Some(SearchField::Reading) => readings_subselect(Box::new(word_entry_readings::reading.ilike(pattern))),
+        Some(SearchField::Note) => Box::new(
+            word_entries::id.eq_any(
+                word_entry_notes::table
+                    .select(word_entry_notes::word_entry_id)
+                    .filter(word_entry_notes::note.ilike(pattern))
+                    .into_boxed()
+            )
+        ),
+    }
+}
+
+/// expression matching word entries for a query in the given mode, the match mode
+/// and field only apply to LIKE based modes
+///
+/// the fuzzy and fulltext expressions must stay in sync with the indexes
+/// created by the `create_search_indexes` migration to be index-backed
+pub fn match_expression(
+    mode: SearchMode,
+    query: &str,
+    match_mode: Option<MatchMode>,
+    field: Option<SearchField>,
+) -> WordEntriesPredicate {
+    match mode {
+        SearchMode::Like => like_expression(field, like_pattern(match_mode, query)),
+        SearchMode::Fuzzy => Box::new(
+            sql::<Bool>("(word_entries.orth % ").bind::<Text, _>(query.to_string())
+                .sql(" OR word_entries.quote % ").bind::<Text, _>(query.to_string())
+                .sql(")")
+        ),
+        SearchMode::Fulltext => Box::new(
+            sql::<Bool>("to_tsvector('simple', word_entries.orth || ' ' || word_entries.quote) \
+                @@ websearch_to_tsquery('simple', ").bind::<Text, _>(query.to_string())
+                .sql(")")
+        ),
+        SearchMode::Reading => readings_subselect(reading_match_expression(query, match_mode)),
+    }
+}
+
+/// relevance score of word entries for a query in the given mode, NULL if unranked
+pub fn score_expression(mode: SearchMode, query: &str) -> WordEntriesScore {
+    match mode {
+        SearchMode::Like | SearchMode::Reading => Box::new(sql::<Nullable<Float>>("NULL::real")),
+        SearchMode::Fuzzy => Box::new(
+            sql::<Nullable<Float>>("GREATEST(similarity(word_entries.orth, ").bind::<Text, _>(query.to_string())
+                .sql("), similarity(word_entries.quote, ").bind::<Text, _>(query.to_string())
+                .sql("))")
+        ),
+        SearchMode::Fulltext => Box::new(
+            sql::<Nullable<Float>>("ts_rank(to_tsvector('simple', word_entries.orth || ' ' || word_entries.quote), \
+                websearch_to_tsquery('simple', ").bind::<Text, _>(query.to_string())
+                .sql("))")
+        ),
+    }
+}
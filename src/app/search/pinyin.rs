@@ -0,0 +1,336 @@
+//! Pinyin normalization between tone-number, tone-mark and toneless forms
+use std::collections::HashSet;
+
+lazy_static::lazy_static! {
+    // list of valid toneless pinyin syllables, with ü written as v
+    static ref SYLLABLES: HashSet<&'static str> = "a ai an ang ao \
+        ba bai ban bang bao bei ben beng bi bian biao bie bin bing bo bu \
+        ca cai can cang cao ce cei cen ceng cha chai chan chang chao che chen cheng chi chong chou \
+        chu chua chuai chuan chuang chui chun chuo ci cong cou cu cuan cui cun cuo \
+        da dai dan dang dao de dei den deng di dia dian diao die ding diu dong dou du duan dui dun duo \
+        e ei en eng er fa fan fang fei fen feng fo fou fu \
+        ga gai gan gang gao ge gei gen geng gong gou gu gua guai guan guang gui gun guo \
+        ha hai han hang hao he hei hen heng hong hou hu hua huai huan huang hui hun huo \
+        ji jia jian jiang jiao jie jin jing jiong jiu ju juan jue jun \
+        ka kai kan kang kao ke kei ken keng kong kou ku kua kuai kuan kuang kui kun kuo \
+        la lai lan lang lao le lei leng li lia lian liang liao lie lin ling liu lo long lou lu luan \
+        lun luo lv lve \
+        ma mai man mang mao me mei men meng mi mian miao mie min ming miu mo mou mu \
+        na nai nan nang nao ne nei nen neng ni nian niang niao nie nin ning niu nong nou nu nuan \
+        nuo nv nve o ou \
+        pa pai pan pang pao pei pen peng pi pian piao pie pin ping po pou pu \
+        qi qia qian qiang qiao qie qin qing qiong qiu qu quan que qun \
+        ran rang rao re ren reng ri rong rou ru rua ruan rui run ruo \
+        sa sai san sang sao se sen seng sha shai shan shang shao she shei shen sheng shi shou shu \
+        shua shuai shuan shuang shui shun shuo si song sou su suan sui sun suo \
+        ta tai tan tang tao te teng ti tian tiao tie ting tong tou tu tuan tui tun tuo \
+        wa wai wan wang wei wen weng wo wu \
+        xi xia xian xiang xiao xie xin xing xiong xiu xu xuan xue xun \
+        ya yan yang yao ye yi yin ying yo yong you yu yuan yue yun \
+        za zai zan zang zao ze zei zen zeng zha zhai zhan zhang zhao zhe zhei zhen zheng zhi zhong \
+        zhou zhu zhua zhuai zhuan zhuang zhui zhun zhuo zi zong zou zu zuan zui zun zuo \
+        r m n ng hm hng".split_whitespace().collect();
+}
+
+/// longest valid pinyin syllable, in letters
+const MAX_SYLLABLE_LENGTH: usize = 6;
+
+/// tone-marked vowels, indexed by tone 1-4
+const MARKED_VOWELS: [(char, [char; 4]); 6] = [
+    ('a', ['ā', 'á', 'ǎ', 'à']),
+    ('e', ['ē', 'é', 'ě', 'è']),
+    ('i', ['ī', 'í', 'ǐ', 'ì']),
+    ('o', ['ō', 'ó', 'ǒ', 'ò']),
+    ('u', ['ū', 'ú', 'ǔ', 'ù']),
+    ('v', ['ǖ', 'ǘ', 'ǚ', 'ǜ']),
+];
+
+/// a parsed pinyin syllable, letters are lowercase with ü written as v
+#[derive(Debug, Clone, PartialEq)]
+struct Syllable {
+    letters: String,
+    tone: Option<u8>,
+    capitalized: bool,
+}
+
+/// a parsed run of text, either pinyin syllables or passed-through text
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Syllables(Vec<Syllable>),
+    Other(String),
+}
+
+/// splits a tone-marked or combining-marked char into its base letter and tone
+fn decompose_char(c: char) -> (char, Option<u8>) {
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    for (base, marked) in MARKED_VOWELS.iter() {
+        if let Some(idx) = marked.iter().position(|&m| m == lower) {
+            return (*base, Some(idx as u8 + 1));
+        }
+    }
+    match lower {
+        'ü' => ('v', None),
+        _ => (lower, None),
+    }
+}
+
+/// tone number for combining diacritics following a vowel
+fn combining_tone(c: char) -> Option<u8> {
+    match c {
+        '\u{0304}' => Some(1),
+        '\u{0301}' => Some(2),
+        '\u{030C}' => Some(3),
+        '\u{0300}' => Some(4),
+        _ => None,
+    }
+}
+
+/// true for characters that may be part of a pinyin run
+fn is_pinyin_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == ':' || combining_tone(c).is_some() ||
+        decompose_char(c).1.is_some() || c == 'ü' || c == 'Ü'
+}
+
+/// splits toneless letters into valid syllables, preferring longer syllables first
+fn segment(letters: &[char]) -> Option<Vec<usize>> {
+    if letters.is_empty() {
+        return Some(Vec::new());
+    }
+    let max_len = letters.len().min(MAX_SYLLABLE_LENGTH);
+    for len in (1..=max_len).rev() {
+        let candidate: String = letters[..len].iter().collect();
+        if SYLLABLES.contains(candidate.as_str()) {
+            if let Some(mut rest) = segment(&letters[len..]) {
+                rest.insert(0, len);
+                return Some(rest);
+            }
+        }
+    }
+    None
+}
+
+/// parses a chunk of pinyin letters (ending in an optional tone digit) into syllables
+fn parse_chunk(chars: &[char]) -> Option<Vec<Syllable>> {
+    // collect base letters, with tones and capitals per letter position
+    let mut letters = Vec::<char>::new();
+    let mut tones = Vec::<Option<u8>>::new();
+    let mut capitals = Vec::<bool>::new();
+    let mut digit_tone = None;
+    for (idx, &c) in chars.iter().enumerate() {
+        if let Some(digit) = c.to_digit(10) {
+            if idx != chars.len() - 1 || digit > 5 {
+                return None;
+            }
+            digit_tone = Some(digit as u8);
+        } else if c == ':' {
+            // cedict writes ü as u:
+            match letters.last_mut() {
+                Some(last) if *last == 'u' => *last = 'v',
+                _ => return None,
+            }
+        } else if let Some(tone) = combining_tone(c) {
+            match tones.last_mut() {
+                Some(last) => *last = Some(tone),
+                None => return None,
+            }
+        } else {
+            let (base, tone) = decompose_char(c);
+            if !base.is_ascii_lowercase() {
+                return None;
+            }
+            letters.push(base);
+            tones.push(tone);
+            capitals.push(c.is_uppercase());
+        }
+    }
+
+    // split into syllables and assign tones
+    let lengths = segment(&letters)?;
+    let mut syllables = Vec::new();
+    let mut offset = 0;
+    for len in lengths.iter() {
+        let span = offset..offset + len;
+        syllables.push(Syllable {
+            letters: letters[span.clone()].iter().collect(),
+            tone: tones[span].iter().filter_map(|t| *t).next(),
+            capitalized: capitals[offset],
+        });
+        offset += len;
+    }
+    if let (Some(tone), Some(last)) = (digit_tone, syllables.last_mut()) {
+        last.tone = Some(tone);
+    }
+    Some(syllables)
+}
+
+/// parses text into pinyin syllable runs and passed-through text
+fn parse(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let start = idx;
+        if is_pinyin_char(chars[idx]) {
+            // a run of pinyin characters, split into chunks after each tone digit
+            while idx < chars.len() && is_pinyin_char(chars[idx]) {
+                idx += 1;
+            }
+            let run = &chars[start..idx];
+            let mut syllables = Vec::new();
+            let mut parsed = true;
+            for chunk in run.split_inclusive(|c| c.is_ascii_digit()) {
+                match parse_chunk(chunk) {
+                    Some(chunk_syllables) => syllables.extend(chunk_syllables),
+                    None => { parsed = false; break; }
+                }
+            }
+            if parsed {
+                tokens.push(Token::Syllables(syllables));
+            } else {
+                tokens.push(Token::Other(run.iter().collect()));
+            }
+        } else {
+            while idx < chars.len() && !is_pinyin_char(chars[idx]) {
+                idx += 1;
+            }
+            tokens.push(Token::Other(chars[start..idx].iter().collect()));
+        }
+    }
+    tokens
+}
+
+/// index of the vowel which carries the tone mark in a syllable
+fn tone_mark_position(letters: &str) -> Option<usize> {
+    if let Some(pos) = letters.find('a').or_else(|| letters.find('e')) {
+        return Some(pos);
+    }
+    if let Some(pos) = letters.find("ou") {
+        return Some(pos);
+    }
+    letters.rfind(|c| "iouv".contains(c))
+}
+
+/// capitalizes the first char of a syllable when needed
+fn apply_capital(text: String, capitalized: bool) -> String {
+    if !capitalized {
+        return text;
+    }
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => text,
+    }
+}
+
+/// renders parsed tokens with a syllable formatting function
+fn render<F: Fn(&Syllable) -> String>(text: &str, format_syllable: F) -> String {
+    parse(text).into_iter().map(|token| match token {
+        Token::Syllables(syllables) => syllables.iter().map(&format_syllable).collect(),
+        Token::Other(other) => other,
+    }).collect()
+}
+
+/// converts pinyin to the tone-number form, e.g. `dàpáidàng` to `da4pai2dang4`
+pub fn to_tone_numbers(text: &str) -> String {
+    render(text, |syllable| {
+        let letters = syllable.letters.replace('v', "u:");
+        let numbered = match syllable.tone {
+            Some(tone) => format!("{}{}", letters, tone),
+            None => letters,
+        };
+        apply_capital(numbered, syllable.capitalized)
+    })
+}
+
+/// converts pinyin to the tone-mark form, e.g. `da4 pai2 dang4` to `dà pái dàng`
+#[allow(dead_code)] // for display by library users, search only needs tone numbers and keys
+pub fn to_tone_marks(text: &str) -> String {
+    render(text, |syllable| {
+        let mark_pos = match syllable.tone {
+            Some(tone) if (1..=4).contains(&tone) => tone_mark_position(&syllable.letters)
+                .map(|pos| (pos, tone)),
+            _ => None,
+        };
+        let marked: String = syllable.letters.chars().enumerate().map(|(idx, c)| {
+            match mark_pos {
+                Some((pos, tone)) if pos == idx => MARKED_VOWELS.iter()
+                    .find(|(base, _)| *base == c)
+                    .map_or(c, |(_, marked)| marked[tone as usize - 1]),
+                _ => if c == 'v' { 'ü' } else { c },
+            }
+        }).collect();
+        apply_capital(marked, syllable.capitalized)
+    })
+}
+
+/// converts pinyin to the toneless form, e.g. `da4 pai2 dang4` to `da pai dang`
+#[allow(dead_code)] // for display by library users, search only needs tone numbers and keys
+pub fn to_toneless(text: &str) -> String {
+    render(text, |syllable| {
+        apply_capital(syllable.letters.replace('v', "ü"), syllable.capitalized)
+    })
+}
+
+/// normalized reading search key, lowercase toneless letters only with ü written as v
+///
+/// tone-number, tone-mark and toneless spellings of a reading share the same key,
+/// e.g. `da4 pai2 dang4`, `dàpáidàng` and `dapaidang` all become `dapaidang`
+pub fn search_key(text: &str) -> String {
+    let mut key = String::new();
+    for c in text.chars() {
+        if c == ':' {
+            if key.ends_with('u') {
+                key.pop();
+                key.push('v');
+            }
+            continue;
+        }
+        let (base, _) = decompose_char(c);
+        if base.is_ascii_lowercase() {
+            key.push(base);
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_tone_numbers() {
+        assert_eq!(to_tone_numbers("dàpáidàng"), "da4pai2dang4");
+        assert_eq!(to_tone_numbers("dà pái dàng"), "da4 pai2 dang4");
+        assert_eq!(to_tone_numbers("Xī'ān"), "Xi1'an1");
+        assert_eq!(to_tone_numbers("lǜsè"), "lu:4se4");
+        assert_eq!(to_tone_numbers("da4 pai2 dang4"), "da4 pai2 dang4");
+    }
+
+    #[test]
+    fn test_to_tone_marks() {
+        assert_eq!(to_tone_marks("da4 pai2 dang4"), "dà pái dàng");
+        assert_eq!(to_tone_marks("da4pai2dang4"), "dàpáidàng");
+        assert_eq!(to_tone_marks("Dao4 de2 jing1"), "Dào dé jīng");
+        assert_eq!(to_tone_marks("lu:4 se4"), "lǜ sè");
+        assert_eq!(to_tone_marks("gou3 liu2 shui3 hui4"), "gǒu liú shuǐ huì");
+        assert_eq!(to_tone_marks("ma5"), "ma");
+    }
+
+    #[test]
+    fn test_to_toneless() {
+        assert_eq!(to_toneless("dàpáidàng"), "dapaidang");
+        assert_eq!(to_toneless("da4 pai2 dang4"), "da pai dang");
+        assert_eq!(to_toneless("nu:3 ren2"), "nü ren");
+    }
+
+    #[test]
+    fn test_search_key() {
+        assert_eq!(search_key("da4 pai2 dang4"), "dapaidang");
+        assert_eq!(search_key("dàpáidàng"), "dapaidang");
+        assert_eq!(search_key("da4pai2dang4"), "dapaidang");
+        assert_eq!(search_key("Da Pai Dang"), "dapaidang");
+        assert_eq!(search_key("lu:4 se4"), "lvse");
+        assert_eq!(search_key("lǜsè"), "lvse");
+        assert_eq!(search_key("zhi1 ren2 zhe3 zhi4 , zi4"), "zhirenzhezhizi");
+    }
+}
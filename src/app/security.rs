@@ -1,24 +1,54 @@
 use rand::Rng;
 use argonautica::{Hasher, Verifier};
+use sha2::{Digest, Sha256};
 
 use crate::app::config;
 use crate::app::errors::ServiceError;
 
-/// Generates a random 32-character url-safe base64 token
-pub fn random_token() -> Result<String, ServiceError> {
+/// Generates a random string of url-safe base64 characters
+fn random_url_safe_string(length: usize) -> String {
     const URL_BASE64_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
                             abcdefghijklmnopqrstuvwxyz\
                             0123456789_-";
     let mut rng = rand::thread_rng();
 
-    let token: String = (0..config::TOKEN_LENGTH)
+    (0..length)
         .map(|_| {
             let idx = rng.gen_range(0, URL_BASE64_CHARSET.len());
             URL_BASE64_CHARSET[idx] as char
         })
-        .collect();
+        .collect()
+}
+
+/// Generates a random 64-character url-safe base64 token
+pub fn random_token() -> Result<String, ServiceError> {
+    Ok(random_url_safe_string(config::TOKEN_LENGTH))
+}
+
+/// Generates a random request id for tracing responses back to server logs
+pub fn random_request_id() -> String {
+    random_url_safe_string(config::REQUEST_ID_LENGTH)
+}
+
+/// Hashes a random token to a sha256 hex digest, for tokens looked up by value
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
 
-    Ok(token)
+/// Checks a text password meets minimum strength rules, reporting failures against the given field
+pub fn validate_password_strength(field: &str, password: &str) -> Result<(), ServiceError> {
+    let password_length = password.chars().count();
+    if !(config::PASSWORD_MIN_LENGTH..=config::PASSWORD_MAX_LENGTH).contains(&password_length) {
+        return Err(ServiceError::invalid_field(field, format!("must be between {} and {} characters",
+            config::PASSWORD_MIN_LENGTH, config::PASSWORD_MAX_LENGTH)));
+    }
+    if !password.chars().any(char::is_alphabetic) || !password.chars().any(|c| c.is_ascii_digit()) {
+        return Err(ServiceError::invalid_field(field,
+            "must contain at least one letter and one digit"));
+    }
+    Ok(())
 }
 
 /// Hashes a text password to argon2-compatible hash
@@ -28,7 +58,7 @@ pub fn hash_password(password: &str) -> Result<String, ServiceError> {
         .with_secret_key(config::SECRET_KEY.as_str())
         .hash()
         .map_err(|err| {
-            dbg!(err);
+            log::error!("password hashing failed: {}", err);
             ServiceError::InternalServerError
         })
 }
@@ -41,7 +71,7 @@ pub fn verify_password(hash: &str, password: &str) -> Result<bool, ServiceError>
         .with_secret_key(config::SECRET_KEY.as_str())
         .verify()
         .map_err(|err| {
-            dbg!(err);
+            log::warn!("password verification failed: {}", err);
             ServiceError::Unauthorized
         })
 }
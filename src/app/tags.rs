@@ -0,0 +1,320 @@
+//! Registry of the tags imported into word_entry_tags, with descriptions for clients
+use serde::{Deserialize, Serialize};
+
+/// kind of information a tag gives about a word entry
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagCategory {
+    /// part of speech or conjugation class
+    Pos,
+    /// field of use, e.g. medicine
+    Domain,
+    /// spelling of the orth, e.g. irregular kanji
+    Orth,
+    /// style or period of use, e.g. archaic or slang
+    Register,
+    /// anything else
+    Misc,
+}
+
+/// dictionary format a tag is imported from
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagSource {
+    Edict,
+    Cedict,
+}
+
+/// registered tag
+#[derive(Debug, Clone, Serialize)]
+pub struct TagInfo {
+    pub tag: &'static str,
+    pub description: &'static str,
+    pub category: TagCategory,
+    pub source: TagSource,
+}
+
+/// edict grammar part-of-speech tags
+const EDICT_POS: &[(&str, &str)] = &[
+    ("adj-f", "noun or verb acting prenominally"),
+    ("adj-i", "adjective (keiyoushi)"),
+    ("adj-ix", "adjective (keiyoushi), yoi/ii class"),
+    ("adj-na", "adjectival noun or quasi-adjective (keiyodoshi)"),
+    ("adj-nari", "archaic/formal form of na-adjective"),
+    ("adj-no", "noun which may take the genitive case particle 'no'"),
+    ("adj-pn", "pre-noun adjectival (rentaishi)"),
+    ("adj-t", "'taru' adjective"),
+    ("adv", "adverb (fukushi)"),
+    ("adv-to", "adverb taking the 'to' particle"),
+    ("aux", "auxiliary"),
+    ("aux-adj", "auxiliary adjective"),
+    ("aux-v", "auxiliary verb"),
+    ("conj", "conjunction"),
+    ("cop", "copula"),
+    ("ctr", "counter"),
+    ("exp", "expression (phrase, clause, etc.)"),
+    ("int", "interjection (kandoushi)"),
+    ("n", "noun (common) (futsuumeishi)"),
+    ("n-adv", "adverbial noun (fukushitekimeishi)"),
+    ("n-pref", "noun, used as a prefix"),
+    ("n-suf", "noun, used as a suffix"),
+    ("n-t", "noun (temporal) (jisoumeishi)"),
+    ("num", "numeric"),
+    ("pn", "pronoun"),
+    ("pref", "prefix"),
+    ("prt", "particle"),
+    ("suf", "suffix"),
+    ("v1", "Ichidan verb"),
+    ("v1-s", "Ichidan verb, kureru special class"),
+    ("v2a-s", "Nidan verb with 'u' ending (archaic)"),
+    ("v2b-k", "Nidan verb (upper class) with 'bu' ending (archaic)"),
+    ("v2d-s", "Nidan verb (lower class) with 'dzu' ending (archaic)"),
+    ("v2g-k", "Nidan verb (upper class) with 'gu' ending (archaic)"),
+    ("v2g-s", "Nidan verb (lower class) with 'gu' ending (archaic)"),
+    ("v2h-k", "Nidan verb (upper class) with 'hu/fu' ending (archaic)"),
+    ("v2h-s", "Nidan verb (lower class) with 'hu/fu' ending (archaic)"),
+    ("v2k-k", "Nidan verb (upper class) with 'ku' ending (archaic)"),
+    ("v2k-s", "Nidan verb (lower class) with 'ku' ending (archaic)"),
+    ("v2m-s", "Nidan verb (lower class) with 'mu' ending (archaic)"),
+    ("v2n-s", "Nidan verb (lower class) with 'nu' ending (archaic)"),
+    ("v2r-k", "Nidan verb (upper class) with 'ru' ending (archaic)"),
+    ("v2r-s", "Nidan verb (lower class) with 'ru' ending (archaic)"),
+    ("v2s-s", "Nidan verb (lower class) with 'su' ending (archaic)"),
+    ("v2t-k", "Nidan verb (upper class) with 'tsu' ending (archaic)"),
+    ("v2t-s", "Nidan verb (lower class) with 'tsu' ending (archaic)"),
+    ("v2w-s", "Nidan verb (lower class) with 'u' ending and 'we' conjugation (archaic)"),
+    ("v2y-k", "Nidan verb (upper class) with 'yu' ending (archaic)"),
+    ("v2y-s", "Nidan verb (lower class) with 'yu' ending (archaic)"),
+    ("v2z-s", "Nidan verb (lower class) with 'zu' ending (archaic)"),
+    ("v4b", "Yodan verb with 'bu' ending (archaic)"),
+    ("v4g", "Yodan verb with 'gu' ending (archaic)"),
+    ("v4h", "Yodan verb with 'hu/fu' ending (archaic)"),
+    ("v4k", "Yodan verb with 'ku' ending (archaic)"),
+    ("v4m", "Yodan verb with 'mu' ending (archaic)"),
+    ("v4r", "Yodan verb with 'ru' ending (archaic)"),
+    ("v4s", "Yodan verb with 'su' ending (archaic)"),
+    ("v4t", "Yodan verb with 'tsu' ending (archaic)"),
+    ("v5aru", "Godan verb, -aru special class"),
+    ("v5b", "Godan verb with 'bu' ending"),
+    ("v5g", "Godan verb with 'gu' ending"),
+    ("v5k", "Godan verb with 'ku' ending"),
+    ("v5k-s", "Godan verb, iku/yuku special class"),
+    ("v5m", "Godan verb with 'mu' ending"),
+    ("v5n", "Godan verb with 'nu' ending"),
+    ("v5r", "Godan verb with 'ru' ending"),
+    ("v5r-i", "Godan verb with 'ru' ending (irregular verb)"),
+    ("v5s", "Godan verb with 'su' ending"),
+    ("v5t", "Godan verb with 'tsu' ending"),
+    ("v5u", "Godan verb with 'u' ending"),
+    ("v5u-s", "Godan verb with 'u' ending (special class)"),
+    ("vi", "intransitive verb"),
+    ("vk", "Kuru verb, special class"),
+    ("vn", "irregular nu verb"),
+    ("vr", "irregular ru verb, plain form ends with -ri"),
+    ("vs", "noun or participle which takes the auxiliary verb suru"),
+    ("vs-c", "su verb, precursor to the modern suru"),
+    ("vs-i", "suru verb, included"),
+    ("vs-s", "suru verb, special class"),
+    ("vt", "transitive verb"),
+    ("vz", "Ichidan verb, zuru verb (alternative form of -jiru verbs)"),
+];
+
+/// edict tags on the spelling of an orth
+const EDICT_ORTH_TAGS: &[(&str, &str)] = &[
+    ("P", "common word"),
+    ("ik", "word containing irregular kana usage"),
+    ("iK", "word containing irregular kanji usage"),
+    ("io", "irregular okurigana usage"),
+    ("ateji", "ateji (phonetic) reading"),
+    ("nokanji", "reading not a true reading of the kanji spellings"),
+    ("ok", "out-dated or obsolete kana usage"),
+    ("oK", "word containing out-dated kanji"),
+    ("oik", "old or irregular kana form"),
+    ("rK", "rarely-used kanji form"),
+    ("rk", "rarely-used kana form"),
+    ("sK", "search-only kanji form"),
+    ("sk", "search-only kana form"),
+];
+
+/// edict {bracket} field of use tags
+const EDICT_DOMAIN_TAGS: &[(&str, &str)] = &[
+    ("anat", "anatomy"),
+    ("archit", "architecture"),
+    ("astron", "astronomy"),
+    ("baseb", "baseball"),
+    ("biol", "biology"),
+    ("bot", "botany"),
+    ("Buddh", "Buddhism"),
+    ("bus", "business"),
+    ("chem", "chemistry"),
+    ("Christn", "Christianity"),
+    ("comp", "computing"),
+    ("econ", "economics"),
+    ("engr", "engineering"),
+    ("finc", "finance"),
+    ("food", "food, cooking"),
+    ("geol", "geology"),
+    ("geom", "geometry"),
+    ("law", "law"),
+    ("ling", "linguistics"),
+    ("MA", "martial arts"),
+    ("mahj", "mahjong"),
+    ("math", "mathematics"),
+    ("med", "medicine"),
+    ("mil", "military"),
+    ("music", "music"),
+    ("physics", "physics"),
+    ("Shinto", "Shinto"),
+    ("shogi", "shogi"),
+    ("sports", "sports"),
+    ("sumo", "sumo"),
+    ("zool", "zoology"),
+];
+
+/// jmdict misc tags on the style or period of use of a sense
+const EDICT_REGISTER_TAGS: &[(&str, &str)] = &[
+    ("arch", "archaic"),
+    ("chn", "children's language"),
+    ("col", "colloquial"),
+    ("dated", "dated term"),
+    ("derog", "derogatory"),
+    ("euph", "euphemistic"),
+    ("fam", "familiar language"),
+    ("fem", "female term or language"),
+    ("form", "formal or literary term"),
+    ("hist", "historical term"),
+    ("hon", "honorific or respectful (sonkeigo) language"),
+    ("hum", "humble (kenjougo) language"),
+    ("joc", "jocular, humorous term"),
+    ("m-sl", "manga slang"),
+    ("male", "male term or language"),
+    ("net-sl", "Internet slang"),
+    ("obs", "obsolete term"),
+    ("poet", "poetical term"),
+    ("pol", "polite (teineigo) language"),
+    ("rare", "rare term"),
+    ("sens", "sensitive"),
+    ("sl", "slang"),
+    ("vulg", "vulgar expression or word"),
+    ("X", "rude or X-rated term"),
+];
+
+/// jmdict dial tags on the regional use of a sense
+const EDICT_DIALECT_TAGS: &[(&str, &str)] = &[
+    ("bra", "Brazilian"),
+    ("hob", "Hokkaido-ben"),
+    ("ksb", "Kansai-ben"),
+    ("ktb", "Kantou-ben"),
+    ("kyb", "Kyoto-ben"),
+    ("kyu", "Kyuushuu-ben"),
+    ("nab", "Nagano-ben"),
+    ("osb", "Osaka-ben"),
+    ("rkb", "Ryuukyuu-ben"),
+    ("thb", "Touhoku-ben"),
+    ("tsb", "Tosa-ben"),
+    ("tsug", "Tsugaru-ben"),
+];
+
+/// other jmdict misc tags
+const EDICT_MISC_TAGS: &[(&str, &str)] = &[
+    ("abbr", "abbreviation"),
+    ("id", "idiomatic expression"),
+    ("on-mim", "onomatopoeic or mimetic word"),
+    ("proverb", "proverb"),
+    ("quote", "quotation"),
+    ("uk", "word usually written using kana alone"),
+    ("yoji", "yojijukugo"),
+];
+
+/// cedict part-of-speech tags
+const CEDICT_POS: &[(&str, &str)] = &[
+    ("grammar", "grammatical word or construction"),
+    ("onom.", "onomatopoeia"),
+];
+
+/// cedict field of use tags
+const CEDICT_DOMAIN_TAGS: &[(&str, &str)] = &[
+    ("anatomy", "anatomy"),
+    ("behavior", "behavior"),
+    ("botany", "botany"),
+    ("brand", "brand name"),
+    ("Buddhism", "Buddhism"),
+    ("chemistry", "chemistry"),
+    ("computing", "computing"),
+    ("finance", "finance"),
+    ("geology", "geology"),
+    ("law", "law"),
+    ("linguistics", "linguistics"),
+    ("math.", "mathematics"),
+    ("medicine", "medicine"),
+    ("military", "military"),
+    ("music", "music"),
+    ("name", "proper name"),
+    ("physics", "physics"),
+    ("sports", "sports"),
+];
+
+/// cedict style, period and regional use tags
+const CEDICT_REGISTER_TAGS: &[(&str, &str)] = &[
+    ("archaic", "archaic"),
+    ("Cantonese", "Cantonese usage"),
+    ("coll.", "colloquial"),
+    ("colloquial", "colloquial"),
+    ("derog.", "derogatory"),
+    ("dialect", "dialect"),
+    ("fig.", "figurative"),
+    ("honorific", "honorific"),
+    ("idiom", "idiom"),
+    ("Internet slang", "Internet slang"),
+    ("literary", "literary"),
+    ("old", "old usage"),
+    ("polite", "polite"),
+    ("proverb", "proverb"),
+    ("slang", "slang"),
+    ("Tw", "Taiwan usage"),
+];
+
+/// other cedict tags
+const CEDICT_MISC_TAGS: &[(&str, &str)] = &[
+    ("character", "single character usage"),
+    ("loanword", "loanword"),
+    ("meaning unclear", "meaning unclear"),
+];
+
+lazy_static::lazy_static! {
+    /// all registered tags
+    pub static ref TAGS: Vec<TagInfo> = {
+        let lists = [
+            (EDICT_POS, TagCategory::Pos, TagSource::Edict),
+            (EDICT_ORTH_TAGS, TagCategory::Orth, TagSource::Edict),
+            (EDICT_DOMAIN_TAGS, TagCategory::Domain, TagSource::Edict),
+            (EDICT_REGISTER_TAGS, TagCategory::Register, TagSource::Edict),
+            (EDICT_DIALECT_TAGS, TagCategory::Register, TagSource::Edict),
+            (EDICT_MISC_TAGS, TagCategory::Misc, TagSource::Edict),
+            (CEDICT_POS, TagCategory::Pos, TagSource::Cedict),
+            (CEDICT_DOMAIN_TAGS, TagCategory::Domain, TagSource::Cedict),
+            (CEDICT_REGISTER_TAGS, TagCategory::Register, TagSource::Cedict),
+            (CEDICT_MISC_TAGS, TagCategory::Misc, TagSource::Cedict),
+        ];
+        lists.iter()
+            .flat_map(|(list, category, source)| list.iter().map(move |(tag, description)| TagInfo {
+                tag,
+                description,
+                category: *category,
+                source: *source,
+            }))
+            .collect()
+    };
+}
+
+/// finds a registered tag of a source dictionary
+#[allow(dead_code)] // used by the import tools
+pub fn find_tag(source: TagSource, tag: &str) -> Option<&'static TagInfo> {
+    TAGS.iter().find(|info| info.source == source && info.tag == tag)
+}
+
+/// true if the tag is registered for the source dictionary in the category
+#[allow(dead_code)] // used by the import tools
+pub fn is_tag(source: TagSource, category: TagCategory, tag: &str) -> bool {
+    find_tag(source, tag).map(|info| info.category) == Some(category)
+}
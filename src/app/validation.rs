@@ -0,0 +1,97 @@
+use actix_web::{error::{JsonPayloadError, QueryPayloadError}, Error, HttpRequest};
+use regex::Regex;
+use serde_json::error::Category;
+
+use crate::app::errors::{FieldError, ServiceError};
+
+lazy_static::lazy_static! {
+    /// field name of serde missing field errors
+    static ref MISSING_FIELD_RE: Regex = Regex::new(r"missing field `([^`]+)`").unwrap();
+}
+
+/// request data that can check its own fields after deserialization
+pub trait Validate {
+    /// checks all fields, reporting every invalid field at once
+    fn validate(&self) -> Result<(), ServiceError>;
+}
+
+/// collects field errors of a request into a single Validation error
+#[derive(Debug, Default)]
+pub struct Validator {
+    field_errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    /// records an error for the field unless the condition holds
+    pub fn check<T: Into<String>>(&mut self, condition: bool, field: &str, message: T) {
+        if !condition {
+            self.field_errors.push(FieldError { field: field.to_string(), message: message.into() });
+        }
+    }
+
+    /// checks a text field is not blank and at most max_length characters
+    pub fn text(&mut self, field: &str, value: &str, max_length: usize) {
+        if value.trim().is_empty() {
+            self.check(false, field, "must not be empty");
+        } else {
+            self.check(value.chars().count() <= max_length, field,
+                format!("must be at most {} characters", max_length));
+        }
+    }
+
+    /// checks an integer field is within an inclusive range
+    pub fn range(&mut self, field: &str, value: i64, min: i64, max: i64) {
+        self.check((min..=max).contains(&value), field, format!("must be between {} and {}", min, max));
+    }
+
+    /// records the field errors of a single field validation function
+    pub fn merge(&mut self, result: Result<(), ServiceError>) -> Result<(), ServiceError> {
+        match result {
+            Ok(()) => Ok(()),
+            Err(ServiceError::Validation(field_errors)) => {
+                self.field_errors.extend(field_errors);
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Validation error listing all recorded field errors, if any
+    pub fn finish(self) -> Result<(), ServiceError> {
+        if self.field_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ServiceError::Validation(self.field_errors))
+        }
+    }
+}
+
+/// converts a serde error message into a field error, naming the field when serde does
+fn deserialize_field_error(field: &str, message: &str) -> FieldError {
+    match MISSING_FIELD_RE.captures(message) {
+        Some(caps) => FieldError { field: caps[1].to_string(), message: "is required".to_string() },
+        None => FieldError { field: field.to_string(), message: message.to_string() },
+    }
+}
+
+/// web::QueryConfig error handler, reports query string errors as Validation errors
+pub fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> Error {
+    match err {
+        QueryPayloadError::Deserialize(ref de_err) =>
+            ServiceError::Validation(vec![deserialize_field_error("query_string", &de_err.to_string())]).into(),
+    }
+}
+
+/// web::JsonConfig error handler, reports json bodies with missing or mistyped fields as Validation errors,
+/// syntax errors stay plain bad requests
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> Error {
+    match err {
+        JsonPayloadError::Deserialize(ref json_err) if json_err.classify() == Category::Data =>
+            ServiceError::Validation(vec![deserialize_field_error("body", &json_err.to_string())]).into(),
+        _ => err.into(),
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Integer, Text};
+
+use crate::app::models::{NewWordEntry, NewWordEntryGroup, NewWordEntryNote, NewWordEntryReading,
+    NewWordEntryTag};
+use crate::schema;
+
+/// word entries buffered before a batch is written
+pub const DEFAULT_BATCH_SIZE: usize = 2000;
+/// rows per INSERT statement, keeps the bind parameters below postgres' limit of 65535
+pub const MAX_ROWS_PER_INSERT: usize = 5000;
+
+/// id reserved from a table's sequence
+#[derive(QueryableByName)]
+struct ReservedId {
+    #[sql_type = "Integer"]
+    id: i32,
+}
+
+/// counts of rows written by a BulkWriter
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BulkWriterStats {
+    pub groups: usize,
+    pub entries: usize,
+    pub notes: usize,
+    pub readings: usize,
+    pub tags: usize,
+    /// groups deleted or replaced by an update, with all their entries
+    pub deleted_groups: usize,
+}
+
+impl BulkWriterStats {
+    /// total rows written to all tables
+    pub fn rows(&self) -> usize {
+        self.groups + self.entries + self.notes + self.readings + self.tags
+    }
+}
+
+/// buffers the rows of a dictionary import and writes them in batches of multi-row inserts
+///
+/// the whole import should run inside `conn.transaction`, so an import that fails or is
+/// interrupted leaves nothing behind. group and entry ids are reserved from their sequences
+/// up front, so child rows can reference them before they are written.
+/// groups of a previous import can be replaced or deleted, keeping the ids of replaced groups.
+pub struct BulkWriter<'a> {
+    conn: &'a PgConnection,
+    batch_size: usize,
+    group_ids: VecDeque<i32>,
+    entry_ids: VecDeque<i32>,
+    deleted_group_ids: Vec<i32>,
+    groups: Vec<(i32, NewWordEntryGroup)>,
+    entries: Vec<(i32, NewWordEntry)>,
+    notes: Vec<NewWordEntryNote>,
+    readings: Vec<NewWordEntryReading>,
+    tags: Vec<NewWordEntryTag>,
+    stats: BulkWriterStats,
+    started_at: Instant,
+}
+
+impl<'a> BulkWriter<'a> {
+    /// writes batches of DEFAULT_BATCH_SIZE entries
+    pub fn new(conn: &'a PgConnection) -> Self {
+        BulkWriter::with_batch_size(conn, DEFAULT_BATCH_SIZE)
+    }
+
+    /// writes batches of batch_size entries
+    pub fn with_batch_size(conn: &'a PgConnection, batch_size: usize) -> Self {
+        BulkWriter {
+            conn,
+            batch_size: batch_size.max(1),
+            group_ids: VecDeque::new(),
+            entry_ids: VecDeque::new(),
+            deleted_group_ids: Vec::new(),
+            groups: Vec::new(),
+            entries: Vec::new(),
+            notes: Vec::new(),
+            readings: Vec::new(),
+            tags: Vec::new(),
+            stats: BulkWriterStats::default(),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// connection the import runs on, for other statements belonging to the import
+    pub fn conn(&self) -> &'a PgConnection {
+        self.conn
+    }
+
+    /// reserves a block of ids from a table's id sequence
+    fn reserve_ids(&self, sequence: &str, count: usize) -> QueryResult<VecDeque<i32>> {
+        let reserved = sql_query("SELECT nextval($1::regclass)::integer AS id FROM generate_series(1, $2)")
+            .bind::<Text, _>(sequence)
+            .bind::<Integer, _>(count as i32)
+            .load::<ReservedId>(self.conn)?;
+        Ok(reserved.into_iter().map(|reserved| reserved.id).collect())
+    }
+
+    /// buffers a word_entry_groups record, returning its id
+    ///
+    /// groups start a new headword, so a full batch is written before the group is added
+    pub fn add_group(&mut self, new_group: NewWordEntryGroup) -> QueryResult<i32> {
+        if self.entries.len() >= self.batch_size {
+            self.flush()?;
+        }
+        if self.group_ids.is_empty() {
+            self.group_ids = self.reserve_ids("word_entry_groups_id_seq", self.batch_size)?;
+        }
+        let group_id = self.group_ids.pop_front().unwrap();
+        self.groups.push((group_id, new_group));
+        Ok(group_id)
+    }
+
+    /// buffers a word_entry_groups record replacing an existing group of the same id,
+    /// the existing group and its entries are deleted before the batch is written
+    pub fn replace_group(&mut self, group_id: i32, new_group: NewWordEntryGroup) -> QueryResult<()> {
+        if self.entries.len() >= self.batch_size {
+            self.flush()?;
+        }
+        self.deleted_group_ids.push(group_id);
+        self.groups.push((group_id, new_group));
+        Ok(())
+    }
+
+    /// deletes an existing group and its entries with the next batch
+    pub fn delete_group(&mut self, group_id: i32) {
+        self.deleted_group_ids.push(group_id);
+    }
+
+    /// buffers a word_entries record, returning its id
+    pub fn add_entry(&mut self, new_entry: NewWordEntry) -> QueryResult<i32> {
+        if self.entry_ids.is_empty() {
+            self.entry_ids = self.reserve_ids("word_entries_id_seq", self.batch_size)?;
+        }
+        let word_entry_id = self.entry_ids.pop_front().unwrap();
+        self.entries.push((word_entry_id, new_entry));
+        Ok(word_entry_id)
+    }
+
+    /// buffers a word_entry_notes record
+    pub fn add_note(&mut self, word_entry_id: i32, note: String) {
+        self.notes.push(NewWordEntryNote { word_entry_id, note });
+    }
+
+    /// buffers a word_entry_readings record
+    pub fn add_reading(&mut self, word_entry_id: i32, reading: String, reading_tag: Option<String>, search_key: Option<String>) {
+        self.readings.push(NewWordEntryReading { word_entry_id, reading, reading_tag, search_key });
+    }
+
+    /// buffers a word_entry_tags record
+    pub fn add_tag(&mut self, word_entry_id: i32, tag: String) {
+        self.tags.push(NewWordEntryTag { word_entry_id, tag });
+    }
+
+    /// writes all buffered rows and reports throughput
+    pub fn flush(&mut self) -> QueryResult<()> {
+        use schema::{word_entries, word_entry_groups, word_entry_notes, word_entry_readings, word_entry_tags};
+
+        // delete replaced groups first, so their ids can be written again
+        for chunk in self.deleted_group_ids.chunks(MAX_ROWS_PER_INSERT) {
+            let word_entry_ids = || word_entries::table
+                .filter(word_entries::group_id.eq_any(chunk))
+                .select(word_entries::id);
+            diesel::delete(word_entry_notes::table
+                .filter(word_entry_notes::word_entry_id.eq_any(word_entry_ids())))
+                .execute(self.conn)?;
+            diesel::delete(word_entry_readings::table
+                .filter(word_entry_readings::word_entry_id.eq_any(word_entry_ids())))
+                .execute(self.conn)?;
+            diesel::delete(word_entry_tags::table
+                .filter(word_entry_tags::word_entry_id.eq_any(word_entry_ids())))
+                .execute(self.conn)?;
+            diesel::delete(word_entries::table
+                .filter(word_entries::group_id.eq_any(chunk)))
+                .execute(self.conn)?;
+            diesel::delete(word_entry_groups::table
+                .filter(word_entry_groups::id.eq_any(chunk)))
+                .execute(self.conn)?;
+        }
+
+        for chunk in self.groups.chunks(MAX_ROWS_PER_INSERT) {
+            let rows = chunk.iter()
+                .map(|(group_id, new_group)| (word_entry_groups::id.eq(group_id), new_group))
+                .collect::<Vec<_>>();
+            diesel::insert_into(word_entry_groups::table)
+                .values(rows)
+                .execute(self.conn)?;
+        }
+        for chunk in self.entries.chunks(MAX_ROWS_PER_INSERT) {
+            let rows = chunk.iter()
+                .map(|(word_entry_id, new_entry)| (word_entries::id.eq(word_entry_id), new_entry))
+                .collect::<Vec<_>>();
+            diesel::insert_into(word_entries::table)
+                .values(rows)
+                .execute(self.conn)?;
+        }
+        for chunk in self.notes.chunks(MAX_ROWS_PER_INSERT) {
+            diesel::insert_into(word_entry_notes::table)
+                .values(chunk)
+                .execute(self.conn)?;
+        }
+        for chunk in self.readings.chunks(MAX_ROWS_PER_INSERT) {
+            diesel::insert_into(word_entry_readings::table)
+                .values(chunk)
+                .execute(self.conn)?;
+        }
+        for chunk in self.tags.chunks(MAX_ROWS_PER_INSERT) {
+            diesel::insert_into(word_entry_tags::table)
+                .values(chunk)
+                .execute(self.conn)?;
+        }
+
+        self.stats.deleted_groups += self.deleted_group_ids.len();
+        self.stats.groups += self.groups.len();
+        self.stats.entries += self.entries.len();
+        self.stats.notes += self.notes.len();
+        self.stats.readings += self.readings.len();
+        self.stats.tags += self.tags.len();
+        let wrote_entries = !self.entries.is_empty();
+        self.deleted_group_ids.clear();
+        self.groups.clear();
+        self.entries.clear();
+        self.notes.clear();
+        self.readings.clear();
+        self.tags.clear();
+
+        if wrote_entries {
+            let elapsed = self.started_at.elapsed().as_secs_f64();
+            println!("  wrote {} entries, {} rows in {:.1}s ({:.0} entries/s, {:.0} rows/s)",
+                self.stats.entries, self.stats.rows(), elapsed,
+                self.stats.entries as f64 / elapsed, self.stats.rows() as f64 / elapsed);
+        }
+        Ok(())
+    }
+
+    /// writes the remaining rows, returning the row counts
+    pub fn finish(mut self) -> QueryResult<BulkWriterStats> {
+        self.flush()?;
+
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        println!("Wrote {} groups, {} entries, {} notes, {} readings and {} tags in {:.1}s ({:.0} rows/s)",
+            self.stats.groups, self.stats.entries, self.stats.notes, self.stats.readings, self.stats.tags,
+            elapsed, self.stats.rows() as f64 / elapsed);
+        Ok(self.stats)
+    }
+}
@@ -1,3 +1,10 @@
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+lazy_static::lazy_static! {
+    // captures the sequence number of edict2 EntL ids
+    static ref ENTL_ID_RGX: Regex = Regex::new(r"^EntL(\d+)X?$").unwrap();
+}
 
 /// splits a string by /, ignoring any / within ( ) parentheses
 pub fn split_by_outer_slashes(text: &str) -> Vec<String> {
@@ -100,6 +107,24 @@ pub fn extract_outer_paren_groups(text: &str) -> (String, Vec<String>) {
     (rem_str, collected_groups)
 }
 
+/// stable key of a line's entry across releases of the dictionary
+///
+/// edict2 lines end with an EntL sequence id, cedict lines are keyed by their
+/// "traditional simplified [pinyin]" headword part
+pub fn upstream_key(line_parts: &[String]) -> String {
+    line_parts.iter().rev()
+        .find_map(|part| ENTL_ID_RGX.captures(part.trim()))
+        .map(|caps| format!("EntL{}", &caps[1]))
+        .unwrap_or_else(|| line_parts.first().map_or("", |part| part.trim()).to_string())
+}
+
+/// sha256 hex digest of a dictionary line, for detecting modified entries
+pub fn line_hash(line: &str) -> String {
+    Sha256::digest(line.as_bytes()).iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,4 +159,13 @@ mod tests {
         assert_eq!(rem, "neing and と");
         assert_eq!(groups, vec!["(st)","(some (unicode/ユニコード))"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_upstream_key() {
+        let input = split_by_outer_slashes("食べる [たべる] /(v1,vt) (1) to eat/(2) to live on (e.g. a salary)/EntL1358280X/");
+        assert_eq!(upstream_key(&input), "EntL1358280");
+
+        let input = split_by_outer_slashes("大牌檔 大牌档 [da4 pai2 dang4] /food stall/");
+        assert_eq!(upstream_key(&input), "大牌檔 大牌档 [da4 pai2 dang4]");
+    }
+}
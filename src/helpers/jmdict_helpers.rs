@@ -0,0 +1,472 @@
+use std::cell::RefCell;
+use std::io::{self, BufRead, Read};
+use std::rc::Rc;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, BytesText, Event};
+
+/// ke_pri and re_pri priority codes of common words, marked with the P tag like in edict2
+const COMMON_PRIORITIES: &[&str] = &["news1", "ichi1", "spec1", "spec2", "gai1"];
+
+/// k_ele element, a kanji spelling of an entry
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KanjiElement {
+    pub keb: String,
+    pub ke_inf: Vec<String>,
+    pub ke_pri: Vec<String>,
+}
+
+/// r_ele element, a kana reading of an entry
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReadingElement {
+    pub reb: String,
+    /// the reading is not a true reading of the kanji spellings
+    pub re_nokanji: bool,
+    /// kanji spellings the reading is restricted to, all if empty
+    pub re_restr: Vec<String>,
+    pub re_inf: Vec<String>,
+    pub re_pri: Vec<String>,
+}
+
+/// gloss element, a translation of a sense
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Gloss {
+    pub lang: String,
+    pub text: String,
+}
+
+/// sense element, one meaning of an entry
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Sense {
+    /// kanji spellings the sense is restricted to, all if empty
+    pub stagk: Vec<String>,
+    /// readings the sense is restricted to, all if empty
+    pub stagr: Vec<String>,
+    pub pos: Vec<String>,
+    pub xref: Vec<String>,
+    pub ant: Vec<String>,
+    pub field: Vec<String>,
+    pub misc: Vec<String>,
+    pub s_inf: Vec<String>,
+    /// source language and source word of loanwords
+    pub lsource: Vec<(String, String)>,
+    pub dial: Vec<String>,
+    pub gloss: Vec<Gloss>,
+}
+
+/// entry element of a JMdict file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Entry {
+    pub ent_seq: String,
+    pub k_ele: Vec<KanjiElement>,
+    pub r_ele: Vec<ReadingElement>,
+    pub sense: Vec<Sense>,
+    /// unparsed xml of the entry following its start tag, for detecting modified entries
+    pub raw_xml: String,
+}
+
+/// word entry of a single orth, sense and gloss language, with its tags, notes and readings
+#[derive(Debug, Clone, PartialEq)]
+pub struct JmdictWordEntry {
+    pub orth: String,
+    pub quote: String,
+    pub quote_lang: String,
+    pub sense: i32,
+    pub tags: Vec<String>,
+    pub notes: Vec<String>,
+    /// readings with their comma separated reading tags
+    pub readings: Vec<(String, Option<String>)>,
+}
+
+/// tags of a kanji spelling or reading, its info entities and P for common words
+fn element_tags(inf: &[String], pri: &[String]) -> Vec<String> {
+    let mut tags = inf.to_vec();
+    if pri.iter().any(|code| COMMON_PRIORITIES.contains(&code.as_str())) {
+        tags.push("P".to_string());
+    }
+    tags
+}
+
+impl Entry {
+    /// key of the entry across JMdict releases, the EntL id of the same entry in edict2
+    pub fn upstream_key(&self) -> String {
+        format!("EntL{}", self.ent_seq)
+    }
+
+    /// word entries of each orth, sense and gloss language of the entry
+    ///
+    /// orths are the kanji spellings, or the readings of entries written in kana only
+    pub fn word_entries(&self) -> Vec<JmdictWordEntry> {
+        let has_kanji = !self.k_ele.is_empty();
+        let orths = if has_kanji {
+            self.k_ele.iter().map(|k| (&k.keb, element_tags(&k.ke_inf, &k.ke_pri))).collect::<Vec<_>>()
+        } else {
+            self.r_ele.iter().map(|r| (&r.reb, element_tags(&r.re_inf, &r.re_pri))).collect::<Vec<_>>()
+        };
+
+        let mut word_entries = Vec::new();
+        for (orth, orth_tags) in orths {
+            // readings of a kanji spelling, kana orths are their own reading
+            let readings = if has_kanji {
+                self.r_ele.iter()
+                    .filter(|r| r.re_restr.is_empty() || r.re_restr.contains(orth))
+                    .map(|r| {
+                        // keep readings that are not true readings of the kanji, tagged nokanji
+                        let mut reading_tags = element_tags(&r.re_inf, &r.re_pri);
+                        if r.re_nokanji {
+                            reading_tags.push("nokanji".to_string());
+                        }
+                        let reading_tag = if reading_tags.is_empty() { None } else { Some(reading_tags.join(",")) };
+                        (r.reb.clone(), reading_tag)
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                Vec::new()
+            };
+
+            for (sense_idx, sense) in self.sense.iter().enumerate() {
+                // skip senses restricted to other orths
+                let restrictions = if has_kanji { &sense.stagk } else { &sense.stagr };
+                if !(restrictions.is_empty() || restrictions.contains(orth)) {
+                    continue;
+                }
+
+                let mut tags = orth_tags.clone();
+                tags.extend(sense.pos.iter().cloned());
+                tags.extend(sense.field.iter().cloned());
+                tags.extend(sense.misc.iter().cloned());
+                tags.extend(sense.dial.iter().cloned());
+
+                let mut notes = sense.s_inf.clone();
+                if has_kanji && !sense.stagr.is_empty() {
+                    notes.push(format!("only read as {}", sense.stagr.join(", ")));
+                }
+                notes.extend(sense.xref.iter().map(|xref| format!("See {}", xref)));
+                notes.extend(sense.ant.iter().map(|ant| format!("Antonym: {}", ant)));
+                notes.extend(sense.lsource.iter().map(|(lang, word)| {
+                    if word.is_empty() { lang.clone() } else { format!("{}: {}", lang, word) }
+                }));
+
+                // one entry per gloss language, in order of appearance
+                let mut langs = Vec::<&str>::new();
+                for gloss in &sense.gloss {
+                    if !langs.contains(&gloss.lang.as_str()) {
+                        langs.push(&gloss.lang);
+                    }
+                }
+                for lang in langs {
+                    let quote = sense.gloss.iter()
+                        .filter(|gloss| gloss.lang == lang)
+                        .map(|gloss| gloss.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join("; ");
+                    word_entries.push(JmdictWordEntry {
+                        orth: orth.clone(),
+                        quote,
+                        quote_lang: lang.to_string(),
+                        sense: sense_idx as i32,
+                        tags: tags.clone(),
+                        notes: notes.clone(),
+                        readings: readings.clone(),
+                    });
+                }
+            }
+        }
+        word_entries
+    }
+
+    /// stores the text of a child element
+    fn add_text(&mut self, element: &[u8], text: String) {
+        match element {
+            b"ent_seq" => self.ent_seq = text,
+            b"keb" => if let Some(k) = self.k_ele.last_mut() { k.keb = text },
+            b"ke_inf" => if let Some(k) = self.k_ele.last_mut() { k.ke_inf.push(text) },
+            b"ke_pri" => if let Some(k) = self.k_ele.last_mut() { k.ke_pri.push(text) },
+            b"reb" => if let Some(r) = self.r_ele.last_mut() { r.reb = text },
+            b"re_restr" => if let Some(r) = self.r_ele.last_mut() { r.re_restr.push(text) },
+            b"re_inf" => if let Some(r) = self.r_ele.last_mut() { r.re_inf.push(text) },
+            b"re_pri" => if let Some(r) = self.r_ele.last_mut() { r.re_pri.push(text) },
+            _ => if let Some(sense) = self.sense.last_mut() {
+                match element {
+                    b"stagk" => sense.stagk.push(text),
+                    b"stagr" => sense.stagr.push(text),
+                    b"pos" => sense.pos.push(text),
+                    b"xref" => sense.xref.push(text),
+                    b"ant" => sense.ant.push(text),
+                    b"field" => sense.field.push(text),
+                    b"misc" => sense.misc.push(text),
+                    b"s_inf" => sense.s_inf.push(text),
+                    b"dial" => sense.dial.push(text),
+                    b"lsource" => if let Some(lsource) = sense.lsource.last_mut() { lsource.1 = text },
+                    b"gloss" => if let Some(gloss) = sense.gloss.last_mut() { gloss.text = text },
+                    _ => (),
+                }
+            },
+        }
+    }
+}
+
+/// xml:lang attribute of a gloss or lsource element, eng if not given
+fn xml_lang<B: BufRead>(reader: &Reader<B>, e: &BytesStart) -> String {
+    e.attributes()
+        .filter_map(|attr| attr.ok())
+        .find(|attr| attr.key == b"xml:lang")
+        .and_then(|attr| attr.unescape_and_decode_value(reader).ok())
+        .unwrap_or_else(|| "eng".to_string())
+}
+
+/// text of an element
+///
+/// JMdict codes like pos or misc values are entity references declared in the DTD,
+/// these are returned as the entity name, e.g. `&v5k;` as `v5k`
+fn element_text<B: BufRead>(reader: &Reader<B>, e: &BytesText) -> String {
+    let escaped = reader.decode(e.escaped()).unwrap_or_default().trim().to_string();
+    if escaped.starts_with('&') && escaped.ends_with(';') && !escaped[1..].contains('&') {
+        let name = &escaped[1..escaped.len() - 1];
+        if !["lt", "gt", "amp", "apos", "quot"].contains(&name) && !name.starts_with('#') {
+            return name.to_string();
+        }
+    }
+    e.unescape_and_decode(reader).unwrap_or(escaped)
+}
+
+/// input of the xml reader, keeping a copy of the bytes consumed by it
+struct RecordingReader<B: BufRead> {
+    input: B,
+    recorded: Rc<RefCell<Vec<u8>>>,
+}
+
+impl<B: BufRead> Read for RecordingReader<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.input.read(buf)?;
+        self.recorded.borrow_mut().extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+impl<B: BufRead> BufRead for RecordingReader<B> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.input.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // the buffer is already filled, so this does not read
+        if let Ok(buf) = self.input.fill_buf() {
+            self.recorded.borrow_mut().extend_from_slice(&buf[..amt.min(buf.len())]);
+        }
+        self.input.consume(amt);
+    }
+}
+
+/// reads the entries of a JMdict file one at a time
+pub struct JmdictReader<B: BufRead> {
+    reader: Reader<RecordingReader<B>>,
+    buf: Vec<u8>,
+    /// bytes consumed by the reader since the start tag of the current entry
+    recorded: Rc<RefCell<Vec<u8>>>,
+}
+
+impl<B: BufRead> JmdictReader<B> {
+    pub fn new(input: B) -> Self {
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let mut reader = Reader::from_reader(RecordingReader { input, recorded: recorded.clone() });
+        reader.trim_text(true);
+        reader.expand_empty_elements(true);
+        JmdictReader { reader, buf: Vec::new(), recorded }
+    }
+
+    /// position in the file, for error messages
+    pub fn buffer_position(&self) -> usize {
+        self.reader.buffer_position()
+    }
+
+    /// reads the next entry, None at the end of the file
+    pub fn read_entry(&mut self) -> Result<Option<Entry>, quick_xml::Error> {
+        let mut entry: Option<Entry> = None;
+        // element expecting the next text event
+        let mut element = Vec::<u8>::new();
+
+        loop {
+            let mut finished = false;
+            match self.reader.read_event(&mut self.buf)? {
+                Event::Start(ref e) => {
+                    if let Some(entry) = entry.as_mut() {
+                        match e.name() {
+                            b"k_ele" => entry.k_ele.push(KanjiElement::default()),
+                            b"r_ele" => entry.r_ele.push(ReadingElement::default()),
+                            b"sense" => entry.sense.push(Sense::default()),
+                            b"re_nokanji" => if let Some(r) = entry.r_ele.last_mut() { r.re_nokanji = true },
+                            b"gloss" => {
+                                let lang = xml_lang(&self.reader, e);
+                                if let Some(sense) = entry.sense.last_mut() {
+                                    sense.gloss.push(Gloss { lang, text: String::new() });
+                                }
+                            },
+                            b"lsource" => {
+                                let lang = xml_lang(&self.reader, e);
+                                if let Some(sense) = entry.sense.last_mut() {
+                                    sense.lsource.push((lang, String::new()));
+                                }
+                            },
+                            _ => (),
+                        }
+                    } else if e.name() == b"entry" {
+                        entry = Some(Entry::default());
+                        self.recorded.borrow_mut().clear();
+                    }
+                    element = e.name().to_vec();
+                },
+                Event::Text(ref e) => {
+                    let text = element_text(&self.reader, e);
+                    if let Some(entry) = entry.as_mut() {
+                        entry.add_text(&element, text);
+                    }
+                },
+                Event::End(ref e) => {
+                    match e.name() {
+                        b"entry" => if let Some(entry) = entry.as_mut() {
+                            entry.raw_xml = String::from_utf8_lossy(&self.recorded.borrow()).into_owned();
+                            finished = true;
+                        },
+                        // pos applies to the following senses until the next pos
+                        b"sense" => if let Some(entry) = entry.as_mut() {
+                            let sense_count = entry.sense.len();
+                            if sense_count > 1 && entry.sense[sense_count - 1].pos.is_empty() {
+                                entry.sense[sense_count - 1].pos = entry.sense[sense_count - 2].pos.clone();
+                            }
+                        },
+                        _ => (),
+                    }
+                    element.clear();
+                },
+                Event::Eof => return Ok(None),
+                _ => (),
+            }
+
+            // if we don't keep a borrow elsewhere, we can clear the buffer to keep memory usage low
+            self.buf.clear();
+            if finished {
+                return Ok(entry);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JMDICT_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMdict [
+<!ELEMENT JMdict (entry*)>
+<!ENTITY v5k "Godan verb with 'ku' ending">
+<!ENTITY vt "transitive verb">
+<!ENTITY n "noun (common) (futsuumeishi)">
+<!ENTITY uk "word usually written using kana alone">
+<!ENTITY ksb "Kansai-ben">
+<!ENTITY iK "word containing irregular kanji usage">
+]>
+<JMdict>
+<entry>
+<ent_seq>1000001</ent_seq>
+<k_ele><keb>書く</keb><ke_pri>ichi1</ke_pri></k_ele>
+<k_ele><keb>描く</keb><ke_inf>&iK;</ke_inf></k_ele>
+<r_ele><reb>かく</reb></r_ele>
+<r_ele><reb>えがく</reb><re_restr>描く</re_restr></r_ele>
+<r_ele><reb>カク</reb><re_nokanji/></r_ele>
+<sense>
+<pos>&v5k;</pos>
+<pos>&vt;</pos>
+<gloss>to write</gloss>
+<gloss>to compose</gloss>
+<gloss xml:lang="ger">schreiben</gloss>
+</sense>
+<sense>
+<stagk>描く</stagk>
+<xref>絵</xref>
+<dial>&ksb;</dial>
+<gloss>to draw &amp; paint</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1000002</ent_seq>
+<r_ele><reb>アルバイト</reb><re_pri>gai1</re_pri></r_ele>
+<sense>
+<pos>&n;</pos>
+<misc>&uk;</misc>
+<lsource xml:lang="ger">Arbeit</lsource>
+<gloss>part-time job</gloss>
+</sense>
+<sense>
+<gloss>side job</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+
+    fn read_test_entries() -> Vec<Entry> {
+        let mut reader = JmdictReader::new(JMDICT_XML.as_bytes());
+        let mut entries = Vec::new();
+        while let Some(entry) = reader.read_entry().unwrap() {
+            entries.push(entry);
+        }
+        entries
+    }
+
+    #[test]
+    fn test_read_entry() {
+        let entries = read_test_entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].upstream_key(), "EntL1000001");
+        assert_eq!(entries[0].k_ele[1].ke_inf, vec!["iK"]);
+        assert_eq!(entries[0].r_ele[1].re_restr, vec!["描く"]);
+        assert!(entries[0].r_ele[2].re_nokanji);
+        assert_eq!(entries[0].sense[0].pos, vec!["v5k", "vt"]);
+        assert_eq!(entries[0].sense[0].gloss[2], Gloss { lang: "ger".to_string(), text: "schreiben".to_string() });
+        assert_eq!(entries[0].sense[1].gloss[0].text, "to draw & paint");
+        // pos carries over to following senses
+        assert_eq!(entries[0].sense[1].pos, vec!["v5k", "vt"]);
+        assert_eq!(entries[1].sense[0].lsource, vec![("ger".to_string(), "Arbeit".to_string())]);
+        assert_eq!(entries[1].sense[1].pos, vec!["n"]);
+        // the unparsed entry, including its entity references
+        assert!(entries[1].raw_xml.trim_start().starts_with("<ent_seq>1000002</ent_seq>"));
+        assert!(entries[1].raw_xml.contains("<pos>&n;</pos>"));
+        assert!(entries[1].raw_xml.ends_with("</entry>"));
+        assert!(!entries[1].raw_xml.contains("1000001"));
+    }
+
+    #[test]
+    fn test_word_entries() {
+        let entries = read_test_entries();
+
+        let word_entries = entries[0].word_entries();
+        let summary = word_entries.iter()
+            .map(|we| (we.orth.as_str(), we.sense, we.quote_lang.as_str(), we.quote.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![
+            ("書く", 0, "eng", "to write; to compose"),
+            ("書く", 0, "ger", "schreiben"),
+            ("描く", 0, "eng", "to write; to compose"),
+            ("描く", 0, "ger", "schreiben"),
+            ("描く", 1, "eng", "to draw & paint"),
+        ]);
+        assert_eq!(word_entries[0].tags, vec!["P", "v5k", "vt"]);
+        assert_eq!(word_entries[0].readings, vec![
+            ("かく".to_string(), None),
+            ("カク".to_string(), Some("nokanji".to_string())),
+        ]);
+        assert_eq!(word_entries[2].readings, vec![
+            ("かく".to_string(), None),
+            ("えがく".to_string(), None),
+            ("カク".to_string(), Some("nokanji".to_string())),
+        ]);
+        assert_eq!(word_entries[4].tags, vec!["iK", "v5k", "vt", "ksb"]);
+        assert_eq!(word_entries[4].notes, vec!["See 絵"]);
+
+        let word_entries = entries[1].word_entries();
+        assert_eq!(word_entries.len(), 2);
+        assert_eq!(word_entries[0].orth, "アルバイト");
+        assert_eq!(word_entries[0].tags, vec!["P", "n", "uk"]);
+        assert_eq!(word_entries[0].notes, vec!["ger: Arbeit"]);
+        assert!(word_entries[0].readings.is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use std::io::BufRead;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+
+use crate::app::models::NewKanji;
+
+/// reading types imported from reading elements, other types like pinyin are skipped
+const READING_TYPES: &[&str] = &["ja_on", "ja_kun"];
+
+/// character element of a KANJIDIC2 file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Character {
+    pub kanji: NewKanji,
+    /// reading types and readings, nanori readings have the type nanori
+    pub readings: Vec<(String, String)>,
+    /// meaning languages and meanings
+    pub meanings: Vec<(String, String)>,
+}
+
+/// language code of a m_lang attribute, the ISO 639-2 codes used by word entries
+fn meaning_lang(m_lang: Option<String>) -> String {
+    match m_lang.as_deref() {
+        None | Some("en") => "eng".to_string(),
+        Some("fr") => "fra".to_string(),
+        Some("es") => "spa".to_string(),
+        Some("pt") => "por".to_string(),
+        Some(lang) => lang.to_string(),
+    }
+}
+
+/// value of an attribute of an element
+fn attribute<B: BufRead>(reader: &Reader<B>, e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|attr| attr.ok())
+        .find(|attr| attr.key == key)
+        .and_then(|attr| attr.unescape_and_decode_value(reader).ok())
+}
+
+/// reads the characters of a KANJIDIC2 file one at a time
+pub struct KanjidicReader<B: BufRead> {
+    reader: Reader<B>,
+    buf: Vec<u8>,
+}
+
+impl<B: BufRead> KanjidicReader<B> {
+    pub fn new(input: B) -> Self {
+        let mut reader = Reader::from_reader(input);
+        reader.trim_text(true);
+        KanjidicReader { reader, buf: Vec::new() }
+    }
+
+    /// position in the file, for error messages
+    pub fn buffer_position(&self) -> usize {
+        self.reader.buffer_position()
+    }
+
+    /// reads the next character, None at the end of the file
+    pub fn read_character(&mut self) -> Result<Option<Character>, quick_xml::Error> {
+        let mut character: Option<Character> = None;
+        // element expecting the next text event, with its type attribute
+        let mut element = Vec::<u8>::new();
+        let mut element_type: Option<String> = None;
+
+        loop {
+            let mut finished = false;
+            match self.reader.read_event(&mut self.buf)? {
+                Event::Start(ref e) => {
+                    element = e.name().to_vec();
+                    element_type = match e.name() {
+                        b"character" => {
+                            character = Some(Character::default());
+                            None
+                        },
+                        b"rad_value" => attribute(&self.reader, e, b"rad_type"),
+                        b"reading" => attribute(&self.reader, e, b"r_type"),
+                        b"meaning" => attribute(&self.reader, e, b"m_lang"),
+                        _ => None,
+                    };
+                },
+                Event::Text(ref e) => {
+                    let text = e.unescape_and_decode(&self.reader)?;
+                    if let Some(character) = character.as_mut() {
+                        let kanji = &mut character.kanji;
+                        let number = text.parse::<i32>().ok();
+                        match element.as_slice() {
+                            b"literal" => kanji.literal = text,
+                            // the first stroke count is the accepted one, others are common miscounts
+                            b"stroke_count" if kanji.stroke_count.is_none() => kanji.stroke_count = number,
+                            b"grade" => kanji.grade = number,
+                            b"jlpt" => kanji.jlpt = number,
+                            b"freq" => kanji.frequency = number,
+                            b"rad_value" => match element_type.as_deref() {
+                                Some("classical") => kanji.radical = number,
+                                Some("nelson_c") => kanji.nelson_radical = number,
+                                _ => (),
+                            },
+                            b"reading" => match element_type.as_deref() {
+                                Some(reading_type) if READING_TYPES.contains(&reading_type) =>
+                                    character.readings.push((reading_type.to_string(), text)),
+                                _ => (),
+                            },
+                            b"nanori" => character.readings.push(("nanori".to_string(), text)),
+                            b"meaning" => character.meanings.push((meaning_lang(element_type.clone()), text)),
+                            _ => (),
+                        }
+                    }
+                },
+                Event::End(ref e) => {
+                    finished = e.name() == b"character" && character.is_some();
+                    element.clear();
+                    element_type = None;
+                },
+                Event::Eof => return Ok(None),
+                _ => (),
+            }
+
+            // if we don't keep a borrow elsewhere, we can clear the buffer to keep memory usage low
+            self.buf.clear();
+            if finished {
+                return Ok(character);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KANJIDIC_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE kanjidic2 [
+<!ELEMENT kanjidic2 (header,character*)>
+<!ELEMENT character (literal,codepoint, radical, misc, dic_number?, query_code?, reading_meaning?)*>
+]>
+<kanjidic2>
+<header><file_version>4</file_version></header>
+<character>
+<literal>亜</literal>
+<codepoint><cp_value cp_type="ucs">4e9c</cp_value></codepoint>
+<radical>
+<rad_value rad_type="classical">7</rad_value>
+<rad_value rad_type="nelson_c">1</rad_value>
+</radical>
+<misc>
+<grade>8</grade>
+<stroke_count>7</stroke_count>
+<stroke_count>8</stroke_count>
+<freq>1509</freq>
+<jlpt>1</jlpt>
+</misc>
+<reading_meaning>
+<rmgroup>
+<reading r_type="pinyin">ya4</reading>
+<reading r_type="ja_on">ア</reading>
+<reading r_type="ja_kun">つ.ぐ</reading>
+<meaning>Asia</meaning>
+<meaning>rank next</meaning>
+<meaning m_lang="fr">Asie</meaning>
+</rmgroup>
+<nanori>や</nanori>
+</reading_meaning>
+</character>
+<character>
+<literal>𠮟</literal>
+<radical><rad_value rad_type="classical">30</rad_value></radical>
+<misc><stroke_count>5</stroke_count></misc>
+</character>
+</kanjidic2>
+"#;
+
+    #[test]
+    fn test_read_character() {
+        let mut reader = KanjidicReader::new(KANJIDIC_XML.as_bytes());
+
+        let character = reader.read_character().unwrap().unwrap();
+        assert_eq!(character.kanji, NewKanji {
+            literal: "亜".to_string(),
+            stroke_count: Some(7),
+            grade: Some(8),
+            jlpt: Some(1),
+            frequency: Some(1509),
+            radical: Some(7),
+            nelson_radical: Some(1),
+        });
+        let pairs = |strs: &[(&str, &str)]| strs.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect::<Vec<_>>();
+        assert_eq!(character.readings, pairs(&[("ja_on", "ア"), ("ja_kun", "つ.ぐ"), ("nanori", "や")]));
+        assert_eq!(character.meanings, pairs(&[("eng", "Asia"), ("eng", "rank next"), ("fra", "Asie")]));
+
+        let character = reader.read_character().unwrap().unwrap();
+        assert_eq!(character.kanji.literal, "𠮟");
+        assert_eq!(character.kanji.radical, Some(30));
+        assert_eq!(character.kanji.grade, None);
+        assert!(character.readings.is_empty());
+
+        assert_eq!(reader.read_character().unwrap(), None);
+    }
+}
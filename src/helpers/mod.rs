@@ -1,2 +1,5 @@
+pub mod bulk_writer;
 pub mod edict_helpers;
+pub mod jmdict_helpers;
+pub mod kanjidic_helpers;
 pub mod tool_helpers;
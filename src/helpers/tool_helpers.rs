@@ -1,7 +1,10 @@
+use std::collections::{HashMap, HashSet};
 use diesel::prelude::*;
 
-use crate::app::models::{NewWordEntry, WordEntry, NewWordEntryGroup, WordEntryGroup,
-    NewWordEntryNote, NewWordEntryReading, NewWordEntryTag, NewSource, Source};
+use crate::app::models::{NewSource, NewWordEntryGroup, Source, PasswordReset, Role, User};
+use crate::app::search::reading_search_key;
+use crate::app::security::random_token;
+use crate::helpers::bulk_writer::BulkWriter;
 use crate::schema;
 
 /// finds or creates a sources record citing the dictionary import file
@@ -39,62 +42,250 @@ pub fn update_source<'a>(conn: &PgConnection, source_id: i32) {
         .expect(&format!("Unable to update source {}", source_id));
 }
 
-/// writes a word_entries entry to the database table, returning the row id
-pub fn insert_word_entry<'a>(conn: &PgConnection, new_entry: NewWordEntry) -> i32 {
-    use schema::word_entries;
+/// loads the ids and upstream hashes of a source's groups by upstream key
+pub fn load_upstream_groups(conn: &PgConnection, source_id: i32) -> HashMap<String, (i32, Option<String>)> {
+    use schema::word_entry_groups;
 
-    let inserted: WordEntry = diesel::insert_into(word_entries::table)
-        .values(&new_entry)
-        .get_result(conn)
-        .expect("Error saving word_entries record");
+    word_entry_groups::table
+        .filter(word_entry_groups::source_id.eq(source_id))
+        .filter(word_entry_groups::upstream_key.is_not_null())
+        .select((word_entry_groups::id, word_entry_groups::upstream_key, word_entry_groups::upstream_hash))
+        .load::<(i32, Option<String>, Option<String>)>(conn)
+        .expect("Error loading word_entry_groups records")
+        .into_iter()
+        .filter_map(|(group_id, upstream_key, upstream_hash)| Some((upstream_key?, (group_id, upstream_hash))))
+        .collect()
+}
 
-    inserted.id
+/// the key a stored upstream key was numbered from, `key#2` for the second line sharing `key`
+fn upstream_base_key(upstream_key: &str) -> &str {
+    match upstream_key.rfind('#') {
+        Some(idx) if idx + 1 < upstream_key.len() && upstream_key[idx + 1..].bytes().all(|b| b.is_ascii_digit()) =>
+            &upstream_key[..idx],
+        _ => upstream_key,
+    }
 }
 
-/// writes a word_entry_groups entry to the database table, returning the row id
-pub fn insert_word_entry_group<'a>(conn: &PgConnection, new_group: NewWordEntryGroup) -> i32 {
-    use schema::word_entry_groups;
+/// groups of a source's previous import, updated by the keyed groups of a new import
+///
+/// entries are matched by their base key, the headword of a cedict line, so several lines may
+/// share a key. their groups are told apart by digest, or reused in id order when modified,
+/// and new lines are stored as `key#2`, `key#3`... so keys and ids don't depend on file order.
+/// groups left over after the import are no longer upstream, and are deleted by `finish`
+pub struct UpstreamGroups {
+    source_id: i32,
+    /// ids, stored keys and digests of the previous groups by base key, in id order
+    previous_groups: HashMap<String, Vec<(i32, String, Option<String>)>>,
+    /// stored keys of the previous groups and the groups added since
+    taken_keys: HashSet<String>,
+    added_count: usize,
+    modified_count: usize,
+    unchanged_count: usize,
+}
 
-    let inserted: WordEntryGroup = diesel::insert_into(word_entry_groups::table)
-        .values(&new_group)
-        .get_result(conn)
-        .expect("Error saving word_entry_group record");
+impl UpstreamGroups {
+    /// loads the groups of the previous import of a source
+    pub fn load(conn: &PgConnection, source_id: i32) -> Self {
+        let mut previous_groups = HashMap::<String, Vec<(i32, String, Option<String>)>>::new();
+        let mut taken_keys = HashSet::new();
+        for (upstream_key, (group_id, upstream_hash)) in load_upstream_groups(conn, source_id) {
+            previous_groups.entry(upstream_base_key(&upstream_key).to_string())
+                .or_default()
+                .push((group_id, upstream_key.clone(), upstream_hash));
+            taken_keys.insert(upstream_key);
+        }
+        for groups in previous_groups.values_mut() {
+            groups.sort();
+        }
+        UpstreamGroups { source_id, previous_groups, taken_keys, added_count: 0, modified_count: 0, unchanged_count: 0 }
+    }
+
+    /// keeps the previous group of an entry unchanged since the previous import, returning false if there is none
+    ///
+    /// checking all entries first keeps modified lines from taking the groups of unchanged lines of the same key
+    pub fn keep_unchanged(&mut self, upstream_key: &str, upstream_hash: &str) -> bool {
+        let groups = match self.previous_groups.get_mut(upstream_key) {
+            Some(groups) => groups,
+            None => return false,
+        };
+        match groups.iter().position(|(_, _, previous_hash)| previous_hash.as_deref() == Some(upstream_hash)) {
+            Some(idx) => {
+                groups.remove(idx);
+                self.unchanged_count += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// buffers the word_entry_groups record of an upstream entry, returning the group id its
+    /// entries are added to, or None if the entry is unchanged since the previous import
+    ///
+    /// a modified entry replaces the oldest unmatched group of its key, keeping its id
+    pub fn upsert_keyed_group(&mut self, writer: &mut BulkWriter, upstream_key: String, upstream_hash: String)
+        -> QueryResult<Option<i32>>
+    {
+        if self.keep_unchanged(&upstream_key, &upstream_hash) {
+            return Ok(None);
+        }
+
+        let previous_group = self.previous_groups.get_mut(&upstream_key)
+            .filter(|groups| !groups.is_empty())
+            .map(|groups| groups.remove(0));
+        match previous_group {
+            Some((group_id, stored_key, _)) => {
+                self.modified_count += 1;
+                writer.replace_group(group_id, NewWordEntryGroup {
+                    source_id: self.source_id,
+                    upstream_key: Some(stored_key),
+                    upstream_hash: Some(upstream_hash),
+                })?;
+                Ok(Some(group_id))
+            }
+            None => {
+                // number further lines of the key, skipping keys of groups still to be deleted
+                let mut stored_key = upstream_key.clone();
+                let mut duplicate_idx = 1;
+                while self.taken_keys.contains(&stored_key) {
+                    duplicate_idx += 1;
+                    stored_key = format!("{}#{}", upstream_key, duplicate_idx);
+                }
+                self.taken_keys.insert(stored_key.clone());
+                self.added_count += 1;
+                Ok(Some(writer.add_group(NewWordEntryGroup {
+                    source_id: self.source_id,
+                    upstream_key: Some(stored_key),
+                    upstream_hash: Some(upstream_hash),
+                })?))
+            }
+        }
+    }
 
-    inserted.id
+    /// deletes the groups of entries removed upstream with the next batch, and prints the counts
+    pub fn finish(self, writer: &mut BulkWriter) {
+        let mut deleted_count = 0;
+        for (group_id, _, _) in self.previous_groups.values().flatten() {
+            writer.delete_group(*group_id);
+            deleted_count += 1;
+        }
+        println!("Added {} entries, modified {}, deleted {} and kept {} unchanged",
+            self.added_count, self.modified_count, deleted_count, self.unchanged_count);
+    }
 }
 
-/// writes a word_entry_notes entry to the database table
-pub fn insert_word_entry_note<'a>(conn: &PgConnection, word_entry_id: i32, note: String) {
-    use schema::word_entry_notes;
+/// deletes the groups without an upstream key imported for a source, with their entries, notes,
+/// readings and tags, returning the number of groups deleted
+///
+/// run within the import transaction, so a re-import atomically replaces the previous data
+pub fn delete_unkeyed_source_entries(conn: &PgConnection, source_id: i32) -> usize {
+    use schema::{word_entries, word_entry_groups, word_entry_notes, word_entry_readings, word_entry_tags};
 
-    let new_record = NewWordEntryNote {word_entry_id, note};
+    let group_ids = || word_entry_groups::table
+        .filter(word_entry_groups::source_id.eq(source_id))
+        .filter(word_entry_groups::upstream_key.is_null())
+        .select(word_entry_groups::id);
+    let word_entry_ids = || word_entries::table
+        .filter(word_entries::group_id.eq_any(group_ids()))
+        .select(word_entries::id);
 
-    diesel::insert_into(word_entry_notes::table)
-        .values(&new_record)
+    // delete joined records, then the entries and their groups
+    diesel::delete(word_entry_notes::table
+        .filter(word_entry_notes::word_entry_id.eq_any(word_entry_ids())))
+        .execute(conn)
+        .expect("Error deleting word_entry_notes records");
+    diesel::delete(word_entry_readings::table
+        .filter(word_entry_readings::word_entry_id.eq_any(word_entry_ids())))
+        .execute(conn)
+        .expect("Error deleting word_entry_readings records");
+    diesel::delete(word_entry_tags::table
+        .filter(word_entry_tags::word_entry_id.eq_any(word_entry_ids())))
+        .execute(conn)
+        .expect("Error deleting word_entry_tags records");
+    diesel::delete(word_entries::table
+        .filter(word_entries::group_id.eq_any(group_ids())))
         .execute(conn)
-        .expect("Error saving word_entry_notes record");
+        .expect("Error deleting word_entries records");
+    diesel::delete(word_entry_groups::table
+        .filter(word_entry_groups::source_id.eq(source_id))
+        .filter(word_entry_groups::upstream_key.is_null()))
+        .execute(conn)
+        .expect("Error deleting word_entry_groups records")
 }
 
-/// writes a word_entry_readings entry to the database table
-pub fn insert_word_entry_reading<'a>(conn: &PgConnection, word_entry_id: i32, reading: String, reading_tag: Option<String>) {
-    use schema::word_entry_readings;
+/// readings loaded and updated per statement when recomputing search keys
+const SEARCH_KEY_BATCH_SIZE: i64 = 10000;
 
-    let new_record = NewWordEntryReading {word_entry_id, reading, reading_tag};
+/// recomputes the search keys of all readings of an orth language, returning the number of readings changed
+///
+/// imports only store keys for new or modified entries, this brings existing readings up to date
+pub fn update_reading_search_keys(conn: &PgConnection, lang: &str) -> usize {
+    use diesel::sql_types::{Array, Integer, Nullable, Text};
+    use schema::{word_entries, word_entry_readings};
 
-    diesel::insert_into(word_entry_readings::table)
-        .values(&new_record)
-        .execute(conn)
-        .expect("Error saving word_entry_readings record");
+    let mut updated_count = 0;
+    let mut last_id = 0;
+    loop {
+        let readings = word_entry_readings::table
+            .filter(word_entry_readings::id.gt(last_id))
+            .filter(word_entry_readings::word_entry_id.eq_any(
+                word_entries::table.select(word_entries::id).filter(word_entries::orth_lang.eq(lang))
+            ))
+            .select((word_entry_readings::id, word_entry_readings::reading, word_entry_readings::search_key))
+            .order(word_entry_readings::id)
+            .limit(SEARCH_KEY_BATCH_SIZE)
+            .load::<(i32, String, Option<String>)>(conn)
+            .expect("Error loading word_entry_readings records");
+        last_id = match readings.last() {
+            Some((reading_id, _, _)) => *reading_id,
+            None => return updated_count,
+        };
+
+        let (reading_ids, search_keys): (Vec<i32>, Vec<Option<String>>) = readings.into_iter()
+            .filter_map(|(reading_id, reading, search_key)| {
+                let new_search_key = reading_search_key(lang, &reading);
+                if new_search_key == search_key { None } else { Some((reading_id, new_search_key)) }
+            })
+            .unzip();
+        if reading_ids.is_empty() {
+            continue;
+        }
+        updated_count += diesel::sql_query("UPDATE word_entry_readings SET search_key = keys.search_key \
+            FROM unnest($1, $2) AS keys(id, search_key) \
+            WHERE word_entry_readings.id = keys.id")
+            .bind::<Array<Integer>, _>(reading_ids)
+            .bind::<Array<Nullable<Text>>, _>(search_keys)
+            .execute(conn)
+            .expect("Error updating word_entry_readings records");
+    }
 }
 
-/// writes a word_entry_tags entry to the database table
-pub fn insert_word_entry_tag<'a>(conn: &PgConnection, word_entry_id: i32, tag: String) {
-    use schema::word_entry_tags;
+/// issues a password_resets record for the named user with the reset token, returning None if no such user exists
+pub fn create_password_reset(conn: &PgConnection, user_name: &str) -> Option<(PasswordReset, String)> {
+    use schema::password_resets;
+    use schema::users::dsl::*;
 
-    let new_record = NewWordEntryTag {word_entry_id, tag};
+    let user = users.filter(name.eq(user_name))
+        .first::<User>(conn)
+        .optional()
+        .expect("Error checking users table")?;
 
-    diesel::insert_into(word_entry_tags::table)
-        .values(&new_record)
-        .execute(conn)
-        .expect("Error saving word_entry_tags record");
+    // only the hash of the token is stored, it can't be shown again
+    let token = random_token().expect("Error generating reset token");
+    let new_reset = PasswordReset::create(user.id, &token);
+    let password_reset = diesel::insert_into(password_resets::table)
+        .values(&new_reset)
+        .get_result(conn)
+        .expect("Error saving password_resets record");
+    Some((password_reset, token))
+}
+
+/// sets the role of the named user, returning None if no such user exists
+pub fn set_user_role(conn: &PgConnection, user_name: &str, new_role: Role) -> Option<User> {
+    use schema::users::dsl::*;
+
+    diesel::update(users.filter(name.eq(user_name)))
+        .set(role.eq(new_role.as_str()))
+        .get_result::<User>(conn)
+        .optional()
+        .expect("Error updating users table")
 }
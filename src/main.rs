@@ -14,12 +14,14 @@ mod tests;
 use crate::app::config;
 use crate::app::database::get_database_pool;
 use crate::app::identity::get_identity_service;
+use crate::app::middleware::request_id_middleware::assign_request_id;
 use crate::app::routes::build_routes;
+use crate::app::validation::{json_error_handler, query_error_handler};
 
 /// main
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
-    std::env::set_var("RUST_LOG", "actix_web=info,diesel=debug");
+    std::env::set_var("RUST_LOG", "actix_web=info,diesel=debug,main=info");
     env_logger::init();
     dotenv::dotenv().ok();
 
@@ -32,12 +34,16 @@ async fn main() -> std::io::Result<()> {
         App::new()
             // set up DB pool to be used with web::Data<Pool> extractor
             .data(pool.clone())
+            // request ids and error envelopes
+            .wrap_fn(assign_request_id)
             // enable logger
             .wrap(middleware::Logger::default())
             // identity
             .wrap(get_identity_service())
             // json request parsing config
-            .data(web::JsonConfig::default().limit(4096))
+            .app_data(web::JsonConfig::default().limit(4096).error_handler(json_error_handler))
+            // query string parsing config
+            .app_data(web::QueryConfig::default().error_handler(query_error_handler))
             .configure(build_routes)
     })
     .bind(config::BIND_ADDRESS.as_str())?
@@ -1,3 +1,64 @@
+table! {
+    api_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        name -> Varchar,
+        token_hash -> Varchar,
+        created_at -> Timestamptz,
+        last_used_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    kanji (id) {
+        id -> Int4,
+        literal -> Varchar,
+        stroke_count -> Nullable<Int4>,
+        grade -> Nullable<Int4>,
+        jlpt -> Nullable<Int4>,
+        frequency -> Nullable<Int4>,
+        radical -> Nullable<Int4>,
+        nelson_radical -> Nullable<Int4>,
+    }
+}
+
+table! {
+    kanji_meanings (id) {
+        id -> Int4,
+        kanji_id -> Int4,
+        meaning -> Varchar,
+        meaning_lang -> Varchar,
+    }
+}
+
+table! {
+    kanji_readings (id) {
+        id -> Int4,
+        kanji_id -> Int4,
+        reading -> Varchar,
+        reading_type -> Varchar,
+    }
+}
+
+table! {
+    login_attempts (id) {
+        id -> Int4,
+        user_name -> Varchar,
+        client_ip -> Nullable<Text>,
+        succeeded -> Bool,
+        attempted_at -> Timestamptz,
+    }
+}
+
+table! {
+    password_resets (token_hash) {
+        token_hash -> Varchar,
+        user_id -> Int4,
+        created_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
 table! {
     sessions (token) {
         token -> Varchar,
@@ -22,6 +83,7 @@ table! {
         name -> Varchar,
         passhash -> Varchar,
         created_at -> Timestamptz,
+        role -> Varchar,
     }
 }
 
@@ -41,6 +103,8 @@ table! {
     word_entry_groups (id) {
         id -> Int4,
         source_id -> Int4,
+        upstream_key -> Nullable<Varchar>,
+        upstream_hash -> Nullable<Varchar>,
     }
 }
 
@@ -58,6 +122,7 @@ table! {
         word_entry_id -> Int4,
         reading -> Varchar,
         reading_tag -> Nullable<Varchar>,
+        search_key -> Nullable<Varchar>,
     }
 }
 
@@ -70,6 +135,12 @@ table! {
 }
 
 allow_tables_to_appear_in_same_query!(
+    api_tokens,
+    kanji,
+    kanji_meanings,
+    kanji_readings,
+    login_attempts,
+    password_resets,
     sessions,
     sources,
     users,
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, http::header, test::{call_service, TestRequest}};
+
+    use crate::tests::test_helpers::tests::{create_test_app,create_test_user,login_user};
+    use crate::app::controllers::api_tokens_controller::{CreateApiTokenData,CreateApiTokenResult};
+    use crate::app::models::{ApiToken, SlimUser};
+
+    #[actix_rt::test]
+    async fn test_create_use_and_revoke_api_token() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        create_test_user(&mut app, "api_token_user", "api_token_password1").await;
+        let cookie = login_user(&mut app, "api_token_user", "api_token_password1").await;
+
+        // make create request
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&CreateApiTokenData { name: "cli script".to_string() })
+            .uri("/api_tokens")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let created: CreateApiTokenResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse CreateApiTokenResult from response");
+        assert_eq!(created.api_token.name, "cli script");
+        let bearer = format!("Bearer {}", created.token);
+
+        // expect the token to authenticate without a cookie
+        let req2 = TestRequest::get()
+            .header(header::AUTHORIZATION, bearer.clone())
+            .uri("/auth")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+        let response_body2 = match resp2.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let user: SlimUser = serde_json::from_slice(response_body2)
+            .expect("Failed to parse SlimUser from response");
+        assert_eq!(user.name, "api_token_user");
+
+        // expect the token to be listed as used, without its hash
+        let req3 = TestRequest::get()
+            .header(header::AUTHORIZATION, bearer.clone())
+            .uri("/api_tokens")
+            .to_request();
+        let resp3 = call_service(&mut app, req3).await;
+        assert_eq!(resp3.status(), http::StatusCode::OK);
+        let response_body3 = match resp3.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        assert!(!String::from_utf8_lossy(response_body3).contains("token_hash"));
+        let listed: Vec<ApiToken> = serde_json::from_slice(response_body3)
+            .expect("Failed to parse ApiToken list from response");
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].last_used_at.is_some());
+
+        // revoke the token
+        let req4 = TestRequest::delete()
+            .cookie(cookie.clone())
+            .uri(&format!("/api_tokens/{}", created.api_token.id))
+            .to_request();
+        let resp4 = call_service(&mut app, req4).await;
+        assert_eq!(resp4.status(), http::StatusCode::OK);
+
+        // expect the revoked token to be rejected
+        let req5 = TestRequest::get()
+            .header(header::AUTHORIZATION, bearer.clone())
+            .uri("/auth")
+            .to_request();
+        let resp5 = call_service(&mut app, req5).await;
+        assert_eq!(resp5.status(), http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn test_create_api_token_requires_cookie_session() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        create_test_user(&mut app, "api_token_minter", "api_token_password1").await;
+        let cookie = login_user(&mut app, "api_token_minter", "api_token_password1").await;
+
+        // create token with the session cookie
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&CreateApiTokenData { name: "first token".to_string() })
+            .uri("/api_tokens")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let created: CreateApiTokenResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse CreateApiTokenResult from response");
+
+        // expect the token cannot be used to create more tokens
+        let req2 = TestRequest::post()
+            .header(header::AUTHORIZATION, format!("Bearer {}", created.token))
+            .set_json(&CreateApiTokenData { name: "second token".to_string() })
+            .uri("/api_tokens")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn test_invalid_api_token() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make request with unknown token
+        let req = TestRequest::get()
+            .header(header::AUTHORIZATION, "Bearer not_a_token")
+            .uri("/auth")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect UNAUTHORIZED 401 response
+        assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn test_revoke_other_users_api_token() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        create_test_user(&mut app, "api_token_owner", "api_token_password1").await;
+        create_test_user(&mut app, "api_token_other", "api_token_password1").await;
+        let owner_cookie = login_user(&mut app, "api_token_owner", "api_token_password1").await;
+        let other_cookie = login_user(&mut app, "api_token_other", "api_token_password1").await;
+
+        // create token as owner
+        let req = TestRequest::post()
+            .cookie(owner_cookie.clone())
+            .set_json(&CreateApiTokenData { name: "owner token".to_string() })
+            .uri("/api_tokens")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let created: CreateApiTokenResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse CreateApiTokenResult from response");
+
+        // expect other users cannot revoke it
+        let req2 = TestRequest::delete()
+            .cookie(other_cookie.clone())
+            .uri(&format!("/api_tokens/{}", created.api_token.id))
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::NOT_FOUND);
+    }
+}
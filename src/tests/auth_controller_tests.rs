@@ -1,10 +1,18 @@
 #[cfg(test)]
 mod tests {
-    use actix_web::{http, test::{call_service, TestRequest}};
+    use actix_http::cookie::Cookie;
+    use actix_web::{http, http::header, test::{call_service, TestRequest}};
+    use chrono::{Duration,Utc};
+    use diesel::prelude::*;
 
-    use crate::tests::test_helpers::tests::{create_test_app,login_test_user};
-    use crate::app::controllers::auth_controller::AuthRequestData;
+    use crate::tests::test_helpers::tests::{create_test_app,create_test_user,login_test_user,login_user,test_db_pool,TestApp};
+    use crate::app::controllers::api_tokens_controller::{CreateApiTokenData,CreateApiTokenResult};
+    use crate::app::config;
+    use crate::app::controllers::auth_controller::{AuthRequestData,ChangePasswordRequestData,ResetPasswordRequestData};
+    use crate::app::errors::ErrorResponse;
     use crate::app::middleware::auth_middleware::SessionUser;
+    use crate::app::models::{LoginAttempt, NewLoginAttempt, PasswordReset, User};
+    use crate::app::security::random_token;
 
     #[actix_rt::test]
     async fn test_unauthorized_get_me() {
@@ -21,6 +29,53 @@ mod tests {
         assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
     }
 
+    #[actix_rt::test]
+    async fn test_error_response_envelope() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make unauthorized request with a client request id
+        let req = TestRequest::get()
+            .header("X-Request-Id", "client-request-1")
+            .uri("/auth")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+        assert_eq!(resp.headers().get("X-Request-Id").unwrap(), "client-request-1");
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ErrorResponse = serde_json::from_slice(response_body)
+            .expect("Failed to parse ErrorResponse from response");
+        assert_eq!(parsed_json.code, "unauthorized");
+        assert_eq!(parsed_json.request_id.as_deref(), Some("client-request-1"));
+
+        // make malformed request without a request id
+        let req2 = TestRequest::post()
+            .header(header::CONTENT_TYPE, "application/json")
+            .set_payload("{not json")
+            .uri("/auth")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::BAD_REQUEST);
+        let generated_request_id = resp2.headers().get("X-Request-Id")
+            .expect("Missing X-Request-Id header")
+            .to_str().unwrap().to_string();
+
+        // expect extractor errors in the same envelope, with a generated request id
+        let response_body2 = match resp2.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json2: ErrorResponse = serde_json::from_slice(response_body2)
+            .expect("Failed to parse ErrorResponse from response");
+        assert_eq!(parsed_json2.code, "bad_request");
+        assert_eq!(parsed_json2.request_id, Some(generated_request_id));
+    }
+
     #[actix_rt::test]
     async fn test_login_and_get_me() {
         // setup test app
@@ -101,4 +156,428 @@ mod tests {
         // expect an error
         assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
     }
+
+    /// records failed login attempts as if they just happened
+    fn insert_failed_logins(user_names: &[&str], client_ip: Option<&str>) {
+        use crate::schema::login_attempts;
+
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let new_attempts = user_names.iter().map(|user_name| NewLoginAttempt {
+            user_name: user_name.to_string(),
+            client_ip: client_ip.map(|ip| ip.to_string()),
+            succeeded: false,
+            attempted_at: Utc::now(),
+        }).collect::<Vec<_>>();
+        diesel::insert_into(login_attempts::table)
+            .values(&new_attempts)
+            .execute(conn)
+            .expect("Error inserting login attempts");
+    }
+
+    /// creates a personal api token with a cookie session, returning its Authorization header value
+    async fn create_bearer<A>(mut app: &mut A, cookie: &Cookie<'static>) -> String
+        where A: TestApp
+    {
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&CreateApiTokenData { name: "test token".to_string() })
+            .uri("/api_tokens")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let created: CreateApiTokenResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse CreateApiTokenResult from response");
+        format!("Bearer {}", created.token)
+    }
+
+    /// issues a password reset token for a user by name
+    fn create_reset_token(user_name: &str) -> String {
+        use crate::schema::password_resets;
+        use crate::schema::users::dsl::{name, users};
+
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let user = users.filter(name.eq(user_name)).get_result::<User>(conn)
+            .expect("Error finding test user");
+        let token = random_token().expect("Error generating reset token");
+        diesel::insert_into(password_resets::table)
+            .values(&PasswordReset::create(user.id, &token))
+            .execute(conn)
+            .expect("Error inserting password reset");
+        token
+    }
+
+    #[actix_rt::test]
+    async fn test_change_password() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login as the same user from two devices
+        create_test_user(&mut app, "change_pw_user", "change_pw_password1").await;
+        let cookie = login_user(&mut app, "change_pw_user", "change_pw_password1").await;
+        let other_cookie = login_user(&mut app, "change_pw_user", "change_pw_password1").await;
+
+        // make request with wrong current password
+        let req = TestRequest::put()
+            .cookie(cookie.clone())
+            .set_json(&ChangePasswordRequestData {
+                current_password: "wrong_password1".to_string(),
+                new_password: "change_pw_password2".to_string(),
+            })
+            .uri("/auth/password")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+
+        // make request with weak new password
+        let req2 = TestRequest::put()
+            .cookie(cookie.clone())
+            .set_json(&ChangePasswordRequestData {
+                current_password: "change_pw_password1".to_string(),
+                new_password: "short".to_string(),
+            })
+            .uri("/auth/password")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::BAD_REQUEST);
+
+        // make valid request
+        let req3 = TestRequest::put()
+            .cookie(cookie.clone())
+            .set_json(&ChangePasswordRequestData {
+                current_password: "change_pw_password1".to_string(),
+                new_password: "change_pw_password2".to_string(),
+            })
+            .uri("/auth/password")
+            .to_request();
+        let resp3 = call_service(&mut app, req3).await;
+        assert_eq!(resp3.status(), http::StatusCode::OK);
+
+        // expect the current device to still be logged in, and the other logged out
+        let req4 = TestRequest::get().cookie(cookie.clone()).uri("/auth").to_request();
+        assert_eq!(call_service(&mut app, req4).await.status(), http::StatusCode::OK);
+        let req5 = TestRequest::get().cookie(other_cookie.clone()).uri("/auth").to_request();
+        assert_eq!(call_service(&mut app, req5).await.status(), http::StatusCode::UNAUTHORIZED);
+
+        // expect login to require the new password
+        let req6 = TestRequest::post()
+            .set_json(&AuthRequestData {
+                name: "change_pw_user".to_string(),
+                password: "change_pw_password1".to_string(),
+            })
+            .uri("/auth")
+            .to_request();
+        assert_eq!(call_service(&mut app, req6).await.status(), http::StatusCode::UNAUTHORIZED);
+        login_user(&mut app, "change_pw_user", "change_pw_password2").await;
+    }
+
+    #[actix_rt::test]
+    async fn test_change_password_with_api_token() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        create_test_user(&mut app, "change_pw_token_user", "change_pw_password1").await;
+        let cookie = login_user(&mut app, "change_pw_token_user", "change_pw_password1").await;
+        let bearer = create_bearer(&mut app, &cookie).await;
+
+        // expect api tokens not to be able to change the password
+        let req = TestRequest::put()
+            .header(header::AUTHORIZATION, bearer.clone())
+            .set_json(&ChangePasswordRequestData {
+                current_password: "change_pw_password1".to_string(),
+                new_password: "change_pw_password2".to_string(),
+            })
+            .uri("/auth/password")
+            .to_request();
+        assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::UNAUTHORIZED);
+
+        // expect the session, api token and password to be left alone
+        let req2 = TestRequest::get().cookie(cookie.clone()).uri("/auth").to_request();
+        assert_eq!(call_service(&mut app, req2).await.status(), http::StatusCode::OK);
+        let req3 = TestRequest::get().header(header::AUTHORIZATION, bearer).uri("/auth").to_request();
+        assert_eq!(call_service(&mut app, req3).await.status(), http::StatusCode::OK);
+        login_user(&mut app, "change_pw_token_user", "change_pw_password1").await;
+    }
+
+    #[actix_rt::test]
+    async fn test_change_password_throttles_wrong_passwords() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        create_test_user(&mut app, "change_pw_guess_user", "change_pw_password1").await;
+        let cookie = login_user(&mut app, "change_pw_guess_user", "change_pw_password1").await;
+        let change_request = |current_password: &str| TestRequest::put()
+            .cookie(cookie.clone())
+            .set_json(&ChangePasswordRequestData {
+                current_password: current_password.to_string(),
+                new_password: "change_pw_password2".to_string(),
+            })
+            .uri("/auth/password")
+            .to_request();
+
+        // guess the current password until backing off
+        for guess in 0..config::LOGIN_ACCOUNT_FREE_ATTEMPTS {
+            let req = change_request(&format!("wrong_password{}", guess));
+            assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::UNAUTHORIZED);
+        }
+
+        // expect even the correct password to be refused, like logins
+        let resp = call_service(&mut app, change_request("change_pw_password1")).await;
+        assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        assert!(resp.headers().get(header::RETRY_AFTER).is_some());
+        let req = TestRequest::post()
+            .set_json(&AuthRequestData {
+                name: "change_pw_guess_user".to_string(),
+                password: "change_pw_password1".to_string(),
+            })
+            .uri("/auth")
+            .to_request();
+        assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[actix_rt::test]
+    async fn test_unauthorized_change_password() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make request without session
+        let req = TestRequest::put()
+            .set_json(&ChangePasswordRequestData {
+                current_password: "test_user".to_string(),
+                new_password: "new_password1".to_string(),
+            })
+            .uri("/auth/password")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn test_reset_password() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login and issue reset token
+        create_test_user(&mut app, "reset_pw_user", "reset_pw_password1").await;
+        let cookie = login_user(&mut app, "reset_pw_user", "reset_pw_password1").await;
+        let reset_token = create_reset_token("reset_pw_user");
+
+        // redeem reset token
+        let reset_data = ResetPasswordRequestData {
+            token: reset_token.clone(),
+            new_password: "reset_pw_password2".to_string(),
+        };
+        let req = TestRequest::post()
+            .set_json(&reset_data)
+            .uri("/auth/password_reset")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // expect existing sessions to be logged out
+        let req2 = TestRequest::get().cookie(cookie.clone()).uri("/auth").to_request();
+        assert_eq!(call_service(&mut app, req2).await.status(), http::StatusCode::UNAUTHORIZED);
+
+        // expect login with the new password
+        login_user(&mut app, "reset_pw_user", "reset_pw_password2").await;
+
+        // expect reset token to be single use
+        let req3 = TestRequest::post()
+            .set_json(&reset_data)
+            .uri("/auth/password_reset")
+            .to_request();
+        let resp3 = call_service(&mut app, req3).await;
+        assert_eq!(resp3.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn test_reset_password_revokes_api_tokens() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // create an api token and issue reset token
+        create_test_user(&mut app, "reset_token_user", "reset_pw_password1").await;
+        let cookie = login_user(&mut app, "reset_token_user", "reset_pw_password1").await;
+        let bearer = create_bearer(&mut app, &cookie).await;
+        let reset_token = create_reset_token("reset_token_user");
+
+        // redeem reset token
+        let req2 = TestRequest::post()
+            .set_json(&ResetPasswordRequestData {
+                token: reset_token,
+                new_password: "reset_pw_password2".to_string(),
+            })
+            .uri("/auth/password_reset")
+            .to_request();
+        assert_eq!(call_service(&mut app, req2).await.status(), http::StatusCode::OK);
+
+        // expect the api token to be rejected
+        let req3 = TestRequest::get()
+            .header(header::AUTHORIZATION, bearer)
+            .uri("/auth")
+            .to_request();
+        assert_eq!(call_service(&mut app, req3).await.status(), http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn test_login_backoff() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        create_test_user(&mut app, "throttle_user", "throttle_password1").await;
+        insert_failed_logins(&["throttle_user"; 5], None);
+
+        // expect even the correct password to be refused while backing off
+        let req = TestRequest::post()
+            .set_json(&AuthRequestData {
+                name: "throttle_user".to_string(),
+                password: "throttle_password1".to_string(),
+            })
+            .uri("/auth")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: i64 = resp.headers().get(header::RETRY_AFTER)
+            .expect("Missing Retry-After header")
+            .to_str().unwrap().parse().unwrap();
+        assert!(retry_after > 0 && retry_after <= 4);
+    }
+
+    #[actix_rt::test]
+    async fn test_locked_out_login_error_envelope() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        create_test_user(&mut app, "throttle_envelope_user", "throttle_password1").await;
+        insert_failed_logins(&["throttle_envelope_user"; 5], None);
+
+        // make a locked-out login with a client request id
+        let req = TestRequest::post()
+            .header("X-Request-Id", "client-request-2")
+            .set_json(&AuthRequestData {
+                name: "throttle_envelope_user".to_string(),
+                password: "throttle_password1".to_string(),
+            })
+            .uri("/auth")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+
+        // expect the error headers to survive the envelope, once each
+        assert_eq!(resp.headers().get_all(header::RETRY_AFTER).count(), 1);
+        assert_eq!(resp.headers().get("X-Request-Id").unwrap(), "client-request-2");
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ErrorResponse = serde_json::from_slice(response_body)
+            .expect("Failed to parse ErrorResponse from response");
+        assert_eq!(parsed_json.request_id.as_deref(), Some("client-request-2"));
+    }
+
+    #[actix_rt::test]
+    async fn test_login_success_resets_backoff() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        create_test_user(&mut app, "throttle_reset_user", "throttle_password1").await;
+        let failed_auth_data = AuthRequestData {
+            name: "throttle_reset_user".to_string(),
+            password: "wrong_password1".to_string(),
+        };
+
+        // fail, succeed, then fail again, each within the free attempts
+        for _ in 0..2 {
+            let req = TestRequest::post().set_json(&failed_auth_data).uri("/auth").to_request();
+            assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::UNAUTHORIZED);
+        }
+        login_user(&mut app, "throttle_reset_user", "throttle_password1").await;
+        for _ in 0..2 {
+            let req = TestRequest::post().set_json(&failed_auth_data).uri("/auth").to_request();
+            assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::UNAUTHORIZED);
+        }
+
+        // expect failures before the successful login not to count
+        login_user(&mut app, "throttle_reset_user", "throttle_password1").await;
+    }
+
+    #[actix_rt::test]
+    async fn test_login_purges_old_attempts() {
+        use crate::schema::login_attempts::dsl::{login_attempts, user_name};
+
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // record a failed login from before the throttling window
+        create_test_user(&mut app, "purge_attempts_user", "purge_password1").await;
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        diesel::insert_into(login_attempts)
+            .values(&NewLoginAttempt {
+                user_name: "purge_attempts_user".to_string(),
+                client_ip: None,
+                succeeded: false,
+                attempted_at: Utc::now() - Duration::days(1),
+            })
+            .execute(conn)
+            .expect("Error inserting login attempt");
+
+        // expect the old attempt to be removed on login, keeping the new one
+        login_user(&mut app, "purge_attempts_user", "purge_password1").await;
+        let remaining: Vec<LoginAttempt> = login_attempts
+            .filter(user_name.eq("purge_attempts_user"))
+            .get_results(conn)
+            .expect("Error loading login attempts");
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].succeeded);
+    }
+
+    #[actix_rt::test]
+    async fn test_login_ip_lockout() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // many failed logins for different accounts from one ip
+        let user_names = (0..50).map(|i| format!("sprayed_user_{}", i)).collect::<Vec<_>>();
+        let user_name_refs = user_names.iter().map(|name| name.as_str()).collect::<Vec<_>>();
+        insert_failed_logins(&user_name_refs, Some("10.20.30.40"));
+
+        // expect logins from that ip to be locked out
+        let auth_data = AuthRequestData {
+            name: "test_user".to_string(),
+            password: "test_user".to_string(),
+        };
+        let req = TestRequest::post()
+            .peer_addr("10.20.30.40:4321".parse().unwrap())
+            .set_json(&auth_data)
+            .uri("/auth")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: i64 = resp.headers().get(header::RETRY_AFTER)
+            .expect("Missing Retry-After header")
+            .to_str().unwrap().parse().unwrap();
+        assert!(retry_after > 60);
+
+        // expect forwarding headers from an untrusted peer not to get around the lockout
+        let req_spoofed = TestRequest::post()
+            .peer_addr("10.20.30.40:4321".parse().unwrap())
+            .header("X-Forwarded-For", "10.20.30.99")
+            .set_json(&auth_data)
+            .uri("/auth")
+            .to_request();
+        let resp_spoofed = call_service(&mut app, req_spoofed).await;
+        assert_eq!(resp_spoofed.status(), http::StatusCode::TOO_MANY_REQUESTS);
+
+        // expect other ips to be unaffected
+        let req2 = TestRequest::post()
+            .peer_addr("10.20.30.41:4321".parse().unwrap())
+            .set_json(&auth_data)
+            .uri("/auth")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+    }
 }
@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel::dsl::count_star;
+
+    use langis::app::models::{NewWordEntry, NewWordEntryGroup};
+    use langis::helpers::bulk_writer::BulkWriter;
+    use langis::helpers::tool_helpers;
+    use crate::tests::test_helpers::tests::test_db_pool;
+
+    /// counts the word entries of a source
+    fn count_source_entries(conn: &PgConnection, source_id: i32) -> i64 {
+        use crate::schema::{word_entries, word_entry_groups};
+
+        let group_ids = word_entry_groups::table
+            .filter(word_entry_groups::source_id.eq(source_id))
+            .select(word_entry_groups::id);
+        word_entries::table
+            .filter(word_entries::group_id.eq_any(group_ids))
+            .select(count_star())
+            .first::<i64>(conn)
+            .expect("Error counting word entries")
+    }
+
+    /// buffers a group of two senses, each with a note, reading and tag
+    fn add_test_group(writer: &mut BulkWriter, source_id: i32, orth: &str) -> QueryResult<()> {
+        let group_id = writer.add_group(NewWordEntryGroup::from_source(source_id))?;
+        for sense in 0..2 {
+            let word_entry_id = writer.add_entry(NewWordEntry {
+                orth: orth.to_string(),
+                orth_lang: "bulk".to_string(),
+                quote: format!("{} sense {}", orth, sense),
+                quote_lang: "test".to_string(),
+                sense,
+                group_id,
+            })?;
+            writer.add_note(word_entry_id, format!("{} note", orth));
+            writer.add_reading(word_entry_id, format!("{} reading", orth), None, None);
+            writer.add_tag(word_entry_id, "n".to_string());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_writer_batches() {
+        use crate::schema::{word_entries, word_entry_notes, word_entry_readings, word_entry_tags};
+
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let source = tool_helpers::find_or_create_source(conn, "bulk_writer_batches".to_string());
+
+        // write more groups than fit in a batch
+        let stats = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let mut writer = BulkWriter::with_batch_size(conn, 3);
+            for idx in 0..5 {
+                add_test_group(&mut writer, source.id, &format!("bulk_batches_{}", idx))?;
+            }
+            writer.finish()
+        }).expect("Error writing batches");
+        assert_eq!((stats.groups, stats.entries, stats.notes, stats.readings, stats.tags), (5, 10, 10, 10, 10));
+
+        // expect all rows, with child rows attached to their entries
+        assert_eq!(count_source_entries(conn, source.id), 10);
+        let entry = word_entries::table
+            .filter(word_entries::orth.eq("bulk_batches_4"))
+            .filter(word_entries::sense.eq(1))
+            .first::<(i32, String, String, String, String, i32, i32)>(conn)
+            .expect("Error loading written entry");
+        let note = word_entry_notes::table
+            .filter(word_entry_notes::word_entry_id.eq(entry.0))
+            .select(word_entry_notes::note)
+            .first::<String>(conn)
+            .expect("Error loading written note");
+        assert_eq!(note, "bulk_batches_4 note");
+        let reading_count = word_entry_readings::table
+            .filter(word_entry_readings::word_entry_id.eq(entry.0))
+            .select(count_star())
+            .first::<i64>(conn)
+            .expect("Error counting written readings");
+        assert_eq!(reading_count, 1);
+        let tag_count = word_entry_tags::table
+            .filter(word_entry_tags::word_entry_id.eq(entry.0))
+            .select(count_star())
+            .first::<i64>(conn)
+            .expect("Error counting written tags");
+        assert_eq!(tag_count, 1);
+    }
+
+    #[test]
+    fn test_bulk_writer_rolls_back_failed_import() {
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let source = tool_helpers::find_or_create_source(conn, "bulk_writer_rollback".to_string());
+
+        // write full batches, then fail the import
+        let result = conn.transaction::<(), diesel::result::Error, _>(|| {
+            let mut writer = BulkWriter::with_batch_size(conn, 1);
+            for idx in 0..3 {
+                add_test_group(&mut writer, source.id, &format!("bulk_rollback_{}", idx))?;
+            }
+            writer.flush()?;
+            assert_eq!(count_source_entries(conn, source.id), 6);
+            Err(diesel::result::Error::RollbackTransaction)
+        });
+        assert!(result.is_err());
+
+        // expect nothing to be left behind
+        assert_eq!(count_source_entries(conn, source.id), 0);
+    }
+
+    #[test]
+    fn test_bulk_writer_replaces_source_entries() {
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let other_source = tool_helpers::find_or_create_source(conn, "bulk_writer_replace_other".to_string());
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let mut writer = BulkWriter::new(conn);
+            add_test_group(&mut writer, other_source.id, "bulk_replace_other")?;
+            writer.finish()
+        }).expect("Error writing other source");
+
+        // import the same source twice, replacing the first import
+        for run in 0..2 {
+            let source = conn.transaction::<_, diesel::result::Error, _>(|| {
+                let mut writer = BulkWriter::new(conn);
+                let source = tool_helpers::find_or_create_source(writer.conn(), "bulk_writer_replace".to_string());
+                let replaced_count = tool_helpers::delete_unkeyed_source_entries(writer.conn(), source.id);
+                assert_eq!(replaced_count, run * 3);
+                for idx in 0..3 {
+                    add_test_group(&mut writer, source.id, &format!("bulk_replace_{}", idx))?;
+                }
+                writer.finish()?;
+                Ok(source)
+            }).expect("Error replacing source entries");
+
+            // expect a single copy of the import
+            assert_eq!(count_source_entries(conn, source.id), 6);
+        }
+
+        // expect other sources to be left alone
+        assert_eq!(count_source_entries(conn, other_source.id), 2);
+    }
+
+    #[test]
+    fn test_bulk_writer_updates_keyed_groups() {
+        use crate::schema::word_entries;
+
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let source = tool_helpers::find_or_create_source(conn, "bulk_writer_update".to_string());
+        let keyed_group = |key: &str, hash: &str| NewWordEntryGroup {
+            source_id: source.id,
+            upstream_key: Some(key.to_string()),
+            upstream_hash: Some(hash.to_string()),
+        };
+
+        // initial import of three keyed groups
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let mut writer = BulkWriter::new(conn);
+            for key in &["a", "b", "c"] {
+                let group_id = writer.add_group(keyed_group(key, "v1"))?;
+                writer.add_entry(NewWordEntry {
+                    orth: format!("bulk_update_{}", key),
+                    orth_lang: "bulk".to_string(),
+                    quote: "v1".to_string(),
+                    quote_lang: "test".to_string(),
+                    sense: 0,
+                    group_id,
+                })?;
+            }
+            writer.finish()
+        }).expect("Error writing keyed groups");
+        let previous_groups = tool_helpers::load_upstream_groups(conn, source.id);
+        assert_eq!(previous_groups.len(), 3);
+        assert_eq!(previous_groups["b"].1, Some("v1".to_string()));
+
+        // update modifying b and deleting c
+        let (b_group_id, _) = previous_groups["b"];
+        let stats = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let mut writer = BulkWriter::new(conn);
+            writer.replace_group(b_group_id, keyed_group("b", "v2"))?;
+            writer.add_entry(NewWordEntry {
+                orth: "bulk_update_b".to_string(),
+                orth_lang: "bulk".to_string(),
+                quote: "v2".to_string(),
+                quote_lang: "test".to_string(),
+                sense: 0,
+                group_id: b_group_id,
+            })?;
+            writer.delete_group(previous_groups["c"].0);
+            writer.finish()
+        }).expect("Error updating keyed groups");
+        assert_eq!((stats.groups, stats.entries, stats.deleted_groups), (1, 1, 2));
+
+        // expect stable group ids with the modified entry replaced
+        let groups = tool_helpers::load_upstream_groups(conn, source.id);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["a"], previous_groups["a"]);
+        assert_eq!(groups["b"], (b_group_id, Some("v2".to_string())));
+        let b_quotes = word_entries::table
+            .filter(word_entries::group_id.eq(b_group_id))
+            .select(word_entries::quote)
+            .load::<String>(conn)
+            .expect("Error loading updated entries");
+        assert_eq!(b_quotes, vec!["v2"]);
+        assert_eq!(count_source_entries(conn, source.id), 2);
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test::{call_service, TestRequest}};
+    use diesel::prelude::*;
+
+    use crate::tests::test_helpers::tests::{create_test_app,login_test_editor,test_db_pool,test_group_id};
+    use crate::app::controllers::kanji_controller::KanjiRecord;
+    use crate::app::controllers::word_entries_controller::{CreateWordEntryData,ListWordEntriesResult};
+    use crate::app::models::{Kanji,NewKanji,NewKanjiMeaning,NewKanjiReading};
+
+    /// inserts a kanji record with on and kun readings and an english meaning
+    fn insert_test_kanji(literal: &str, on_reading: &str, kun_reading: &str, meaning: &str) {
+        use crate::schema::{kanji,kanji_meanings,kanji_readings};
+
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let kanji_id = diesel::insert_into(kanji::table)
+            .values(&NewKanji {
+                literal: literal.to_string(),
+                stroke_count: Some(29),
+                grade: Some(8),
+                ..NewKanji::default()
+            })
+            .get_result::<Kanji>(conn)
+            .expect("Error inserting kanji")
+            .id;
+        diesel::insert_into(kanji_readings::table)
+            .values(&vec![
+                NewKanjiReading { kanji_id, reading: on_reading.to_string(), reading_type: "ja_on".to_string() },
+                NewKanjiReading { kanji_id, reading: kun_reading.to_string(), reading_type: "ja_kun".to_string() },
+            ])
+            .execute(conn)
+            .expect("Error inserting kanji readings");
+        diesel::insert_into(kanji_meanings::table)
+            .values(&NewKanjiMeaning { kanji_id, meaning: meaning.to_string(), meaning_lang: "eng".to_string() })
+            .execute(conn)
+            .expect("Error inserting kanji meaning");
+    }
+
+    #[actix_rt::test]
+    async fn test_get_kanji() {
+        // setup test app
+        let mut app = create_test_app().await;
+        insert_test_kanji("鬱", "ウツ", "ふさ.ぐ", "gloom");
+
+        // make request
+        let req = TestRequest::get()
+            .uri("/kanji/%E9%AC%B1")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect success
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: KanjiRecord = serde_json::from_slice(response_body)
+            .expect("Failed to parse KanjiRecord from response");
+
+        // expect the kanji with its readings and meanings
+        assert_eq!(parsed_json.kanji.literal, "鬱");
+        assert_eq!(parsed_json.kanji.stroke_count, Some(29));
+        let readings = parsed_json.kanji_readings.iter()
+            .map(|r| (r.reading_type.as_str(), r.reading.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(readings, vec![("ja_on", "ウツ"), ("ja_kun", "ふさ.ぐ")]);
+        assert_eq!(parsed_json.kanji_meanings[0].meaning, "gloom");
+    }
+
+    #[actix_rt::test]
+    async fn test_get_kanji_errors() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // expect not found for characters not in the dictionary
+        let req = TestRequest::get()
+            .uri("/kanji/%E9%AC%AF")
+            .to_request();
+        assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::NOT_FOUND);
+
+        // expect bad request for more than one character
+        let req = TestRequest::get()
+            .uri("/kanji/ab")
+            .to_request();
+        assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_with_kanji() {
+        // setup test app
+        let mut app = create_test_app().await;
+        let cookie = login_test_editor(&mut app).await.into_owned();
+        insert_test_kanji("鬲", "レキ", "かなえ", "tripod");
+        insert_test_kanji("鬳", "ゲン", "こしき", "steamer");
+
+        // create an entry using the kanji, one of them twice
+        let entry_data = CreateWordEntryData {
+            orth: "鬲鬳鬲kanji_expand".to_string(),
+            orth_lang: "jpn".to_string(),
+            quote: "kanji expansion quote".to_string(),
+            quote_lang: "test".to_string(),
+            sense: 0,
+            group_id: test_group_id(),
+            notes: vec![],
+            readings: vec![],
+            tags: vec![],
+        };
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::CREATED);
+
+        for &(params, ref expected_kanji) in &[("", None), ("&kanji=true", Some(vec!["鬲", "鬳"]))] {
+            // make request
+            let req = TestRequest::get()
+                .uri(&format!("/word_entries?query=%25kanji_expand{}", params))
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::OK);
+
+            // parse json from response
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+                .expect("Failed to parse ListWordEntriesResult from response");
+
+            // expect the kanji of the orth in order of appearance, only when requested
+            assert_eq!(parsed_json.page.len(), 1);
+            let kanji = parsed_json.page[0].kanji.as_ref()
+                .map(|kanji| kanji.iter().map(|k| k.kanji.literal.as_str()).collect::<Vec<_>>());
+            assert_eq!(&kanji, expected_kanji, "params: {:?}", params);
+            if let Some(kanji) = parsed_json.page[0].kanji.as_ref() {
+                assert_eq!(kanji[1].kanji_meanings[0].meaning, "steamer");
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test::{call_service, TestRequest}};
+
+    use crate::tests::test_helpers::tests::{create_test_app,create_test_user,login_test_admin,login_user};
+    use crate::app::controllers::auth_controller::AuthRequestData;
+    use crate::app::models::LoginAttempt;
+
+    #[actix_rt::test]
+    async fn test_list_failed_login_attempts() {
+        // setup test app
+        let mut app = create_test_app().await;
+        let admin_cookie = login_test_admin(&mut app).await.into_owned();
+
+        // fail a login, then succeed
+        create_test_user(&mut app, "attempts_user", "attempts_password1").await;
+        let req = TestRequest::post()
+            .set_json(&AuthRequestData {
+                name: "attempts_user".to_string(),
+                password: "wrong_password1".to_string(),
+            })
+            .uri("/auth")
+            .to_request();
+        assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::UNAUTHORIZED);
+        let cookie = login_user(&mut app, "attempts_user", "attempts_password1").await;
+
+        // expect non-admins cannot list attempts
+        let req2 = TestRequest::get()
+            .cookie(cookie.clone())
+            .uri("/login_attempts")
+            .to_request();
+        assert_eq!(call_service(&mut app, req2).await.status(), http::StatusCode::FORBIDDEN);
+
+        // make list request as admin
+        let req3 = TestRequest::get()
+            .cookie(admin_cookie.clone())
+            .uri("/login_attempts?user_name=attempts_user")
+            .to_request();
+        let resp3 = call_service(&mut app, req3).await;
+        assert_eq!(resp3.status(), http::StatusCode::OK);
+
+        // parse json from response
+        let response_body = match resp3.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: Vec<LoginAttempt> = serde_json::from_slice(response_body)
+            .expect("Failed to parse LoginAttempt list from response");
+
+        // expect only the failed attempt
+        assert_eq!(parsed_json.len(), 1);
+        assert_eq!(parsed_json[0].user_name, "attempts_user");
+        assert!(!parsed_json[0].succeeded);
+    }
+}
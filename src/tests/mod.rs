@@ -1,4 +1,13 @@
 //! Integration tests
 pub mod test_helpers;
+pub mod api_tokens_controller_tests;
 pub mod auth_controller_tests;
+pub mod bulk_writer_tests;
+pub mod kanji_controller_tests;
+pub mod login_attempts_controller_tests;
+pub mod sessions_controller_tests;
+pub mod tags_controller_tests;
+pub mod tool_helpers_tests;
+pub mod users_controller_tests;
 pub mod word_entries_controller_tests;
+pub mod word_entry_groups_controller_tests;
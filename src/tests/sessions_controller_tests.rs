@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test::{call_service, TestRequest}};
+    use chrono::{Duration, Utc};
+    use diesel::prelude::*;
+
+    use crate::tests::test_helpers::tests::{create_test_app,create_test_user,login_user,test_db_pool};
+    use crate::app::controllers::sessions_controller::SessionRecord;
+    use crate::app::models::User;
+    use crate::app::security::hash_token;
+
+    /// gets the id of a user by name
+    fn find_user_id(user_name: &str) -> i32 {
+        use crate::schema::users::dsl::{name, users};
+
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        users.filter(name.eq(user_name)).get_result::<User>(conn)
+            .expect("Error finding test user").id
+    }
+
+    #[actix_rt::test]
+    async fn test_list_and_revoke_sessions() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login as the same user from two devices
+        create_test_user(&mut app, "sessions_user", "sessions_password1").await;
+        let cookie = login_user(&mut app, "sessions_user", "sessions_password1").await;
+        let other_cookie = login_user(&mut app, "sessions_user", "sessions_password1").await;
+
+        // make list request
+        let req = TestRequest::get()
+            .cookie(cookie.clone())
+            .uri("/sessions")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: Vec<SessionRecord> = serde_json::from_slice(response_body)
+            .expect("Failed to parse SessionRecord list from response");
+
+        // expect both sessions, one marked current, without the session cookies
+        assert_eq!(parsed_json.len(), 2);
+        assert_eq!(parsed_json.iter().filter(|s| s.current).count(), 1);
+        let session_tokens = {
+            use crate::schema::sessions::dsl::{sessions, token, user_id};
+            let conn: &PgConnection = &test_db_pool().get().unwrap();
+            sessions.select(token).filter(user_id.eq(find_user_id("sessions_user")))
+                .get_results::<String>(conn).expect("Error finding sessions")
+        };
+        let response_text = String::from_utf8_lossy(response_body);
+        assert!(session_tokens.iter().all(|session_token| !response_text.contains(session_token.as_str())));
+        let other_id = parsed_json.iter().find(|s| !s.current).unwrap().id.clone();
+
+        // revoke the other session
+        let req2 = TestRequest::delete()
+            .cookie(cookie.clone())
+            .uri(&format!("/sessions/{}", other_id))
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+
+        // expect the other device to be logged out
+        let req3 = TestRequest::get()
+            .cookie(other_cookie.clone())
+            .uri("/auth")
+            .to_request();
+        let resp3 = call_service(&mut app, req3).await;
+        assert_eq!(resp3.status(), http::StatusCode::UNAUTHORIZED);
+
+        // expect the current device to still be logged in
+        let req4 = TestRequest::get()
+            .cookie(cookie.clone())
+            .uri("/auth")
+            .to_request();
+        let resp4 = call_service(&mut app, req4).await;
+        assert_eq!(resp4.status(), http::StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_revoke_other_users_session() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login as two different users
+        create_test_user(&mut app, "sessions_owner", "sessions_password1").await;
+        create_test_user(&mut app, "sessions_intruder", "sessions_password1").await;
+        let owner_cookie = login_user(&mut app, "sessions_owner", "sessions_password1").await;
+        let intruder_cookie = login_user(&mut app, "sessions_intruder", "sessions_password1").await;
+
+        // find owner session token
+        let owner_token = {
+            use crate::schema::sessions::dsl::{sessions, token, user_id};
+            let conn: &PgConnection = &test_db_pool().get().unwrap();
+            sessions.select(token).filter(user_id.eq(find_user_id("sessions_owner")))
+                .first::<String>(conn).expect("Error finding owner session")
+        };
+
+        // expect NOT_FOUND revoking a session of another user, by id or by token
+        let req = TestRequest::delete()
+            .cookie(intruder_cookie.clone())
+            .uri(&format!("/sessions/{}", hash_token(&owner_token)))
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+        let req = TestRequest::delete()
+            .cookie(intruder_cookie.clone())
+            .uri(&format!("/sessions/{}", owner_token))
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+        // expect owner to still be logged in
+        let req2 = TestRequest::get()
+            .cookie(owner_cookie.clone())
+            .uri("/auth")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_expired_sessions() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login twice
+        create_test_user(&mut app, "expiry_user", "expiry_password1").await;
+        let idle_cookie = login_user(&mut app, "expiry_user", "expiry_password1").await;
+        let old_cookie = login_user(&mut app, "expiry_user", "expiry_password1").await;
+
+        // age one session past the idle timeout and the other past its absolute lifetime
+        {
+            use crate::schema::sessions::dsl::{created_at, last_accessed_at, sessions, token, user_id};
+            let conn: &PgConnection = &test_db_pool().get().unwrap();
+            let tokens = sessions.select(token).filter(user_id.eq(find_user_id("expiry_user")))
+                .order(token).get_results::<String>(conn).expect("Error finding sessions");
+            assert_eq!(tokens.len(), 2);
+            diesel::update(sessions.find(&tokens[0]))
+                .set(last_accessed_at.eq(Utc::now() - Duration::days(1)))
+                .execute(conn).expect("Error updating session");
+            diesel::update(sessions.find(&tokens[1]))
+                .set(created_at.eq(Utc::now() - Duration::days(30)))
+                .execute(conn).expect("Error updating session");
+        }
+
+        // expect both to be rejected
+        for cookie in [idle_cookie, old_cookie].iter() {
+            let req = TestRequest::get()
+                .cookie(cookie.clone())
+                .uri("/auth")
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+        }
+    }
+}
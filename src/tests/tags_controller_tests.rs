@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test::{call_service, TestRequest}};
+    use serde_json::Value;
+
+    use crate::tests::test_helpers::tests::create_test_app;
+
+    #[actix_rt::test]
+    async fn test_list_tags() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make request
+        let req = TestRequest::get()
+            .uri("/tags")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect success
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: Vec<Value> = serde_json::from_slice(response_body)
+            .expect("Failed to parse tags from response");
+
+        // expect tags of both dictionaries with descriptions
+        let v5k = parsed_json.iter().find(|tag| tag["tag"] == "v5k").expect("Missing v5k tag");
+        assert_eq!(v5k["description"], "Godan verb with 'ku' ending");
+        assert_eq!(v5k["category"], "pos");
+        assert_eq!(v5k["source"], "edict");
+        assert!(parsed_json.iter().any(|tag| tag["tag"] == "archaic" && tag["source"] == "cedict"));
+    }
+
+    #[actix_rt::test]
+    async fn test_list_tags_filtered() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make request for cedict register tags
+        let req = TestRequest::get()
+            .uri("/tags?category=register&source=cedict")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: Vec<Value> = serde_json::from_slice(response_body)
+            .expect("Failed to parse tags from response");
+
+        // expect only matching tags
+        assert!(!parsed_json.is_empty());
+        assert!(parsed_json.iter().all(|tag| tag["category"] == "register" && tag["source"] == "cedict"));
+        assert!(parsed_json.iter().any(|tag| tag["tag"] == "archaic"));
+
+        // expect unknown categories to be rejected
+        let req2 = TestRequest::get()
+            .uri("/tags?category=color")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::BAD_REQUEST);
+    }
+}
@@ -10,17 +10,22 @@ pub mod tests {
     use diesel_migrations::run_pending_migrations;
 
     use crate::app::controllers::auth_controller::{AuthRequestData};
+    use crate::app::controllers::users_controller::{CreateUserRequestData};
     use crate::app::database::{get_database_pool, DbPool};
     use crate::app::identity::get_identity_service;
+    use crate::app::middleware::request_id_middleware::assign_request_id;
     use crate::app::routes::build_routes;
     use crate::app::security::hash_password;
+    use crate::app::validation::{json_error_handler, query_error_handler};
 
     // alias for test app type
     pub trait TestApp = Service<Request = Request, Response = ServiceResponse<Body>, Error = Error>;
 
     /// Testing setup function to reset testing database before any tests are run
     struct TestDbSetup {
-        pool: DbPool
+        pool: DbPool,
+        source_id: i32,
+        group_id: i32,
     }
     impl TestDbSetup {
         fn new() -> Self {
@@ -55,25 +60,42 @@ pub mod tests {
             writeln!(&mut stdout(), "").expect("Failed to print to stdout");
 
             // insert test fixtures
-            // insert user
+            // insert users, with the default role and as editor and admin
             let hashed_test_password = hash_password("test_user").expect("hash_password error");
             diesel::sql_query(format!("INSERT INTO users (name,passhash,created_at) VALUES ('test_user','{}',now())", hashed_test_password))
                 .execute(conn).expect("Error when inserting test user account");
+            for role in &["editor", "admin"] {
+                let hashed_test_password = hash_password(&format!("test_{}", role)).expect("hash_password error");
+                diesel::sql_query(format!("INSERT INTO users (name,passhash,created_at,role) VALUES ('test_{}','{}',now(),'{}')", role, hashed_test_password, role))
+                    .execute(conn).expect("Error when inserting test user account");
+            }
 
-            let test_source_id = 0;
-            // insert word_entry
+            // insert source
             #[derive(QueryableByName)]
-            struct TestEntryResult(
+            struct TestIdResult(
                 #[column_name = "id"]
                 #[sql_type = "Integer"]
                 i32
             );
+            let test_source_id = diesel::sql_query("INSERT INTO sources \
+                (name,last_updated_at) \
+                VALUES ('test_source',now()) \
+                RETURNING id \
+            ").get_result::<TestIdResult>(conn).expect("Error when inserting test source").0;
+            // insert word_entry_group
+            let test_group_id = diesel::sql_query(format!("INSERT INTO word_entry_groups \
+                (source_id) \
+                VALUES ({}) \
+                RETURNING id \
+            ", test_source_id))
+                .get_result::<TestIdResult>(conn).expect("Error when inserting test word_entry_group").0;
+            // insert word_entry
             let test_entry_result = diesel::sql_query(format!("INSERT INTO word_entries \
                 (orth,orth_lang,quote,quote_lang,sense,group_id) \
                 VALUES ('test_orth','test','test quote','test',0,{}) \
                 RETURNING id \
-            ", test_source_id))
-                .get_result::<TestEntryResult>(conn).expect("Error when inserting test word_entry");
+            ", test_group_id))
+                .get_result::<TestIdResult>(conn).expect("Error when inserting test word_entry");
             let test_entry_id = test_entry_result.0;
             // insert word_entry_note
             diesel::sql_query(format!("INSERT INTO word_entry_notes \
@@ -92,7 +114,7 @@ pub mod tests {
             ", test_entry_id)).execute(conn).expect("Error inserting test tag");
 
             // result
-            TestDbSetup { pool }
+            TestDbSetup { pool, source_id: test_source_id, group_id: test_group_id }
         }
     }
     lazy_static::lazy_static! {
@@ -100,16 +122,36 @@ pub mod tests {
     }
 
 
+    /// Gets a connection pool for the test database
+    pub fn test_db_pool() -> DbPool {
+        TEST_DB_POOL.pool.clone()
+    }
+
+    /// Gets the id of the sources test fixture
+    pub fn test_source_id() -> i32 {
+        TEST_DB_POOL.source_id
+    }
+
+    /// Gets the id of the word_entry_groups test fixture
+    pub fn test_group_id() -> i32 {
+        TEST_DB_POOL.group_id
+    }
+
+
     /// Creates App service with test configuration
     pub async fn create_test_app() -> impl TestApp {
         test::init_service(
             App::new()
                 // set up DB pool to be used with web::Data<Pool> extractor
                 .data(TEST_DB_POOL.pool.clone())
+                // request ids and error envelopes
+                .wrap_fn(assign_request_id)
                 // identity
                 .wrap(get_identity_service())
                 // json request parsing config
-                .data(web::JsonConfig::default().limit(4096))
+                .app_data(web::JsonConfig::default().limit(4096).error_handler(json_error_handler))
+                // query string parsing config
+                .app_data(web::QueryConfig::default().error_handler(query_error_handler))
                 .configure(build_routes)
         )
         .await
@@ -117,13 +159,55 @@ pub mod tests {
 
 
     /// Logs-in with test account, returns associated cookie
-    pub async fn login_test_user<A>(mut app: &mut A) -> Cookie<'_>
+    pub async fn login_test_user<A>(app: &mut A) -> Cookie<'_>
+        where A: TestApp
+    {
+        login_user(app, "test_user", "test_user").await
+    }
+
+    /// Logs-in with test account of the editor role, returns associated cookie
+    pub async fn login_test_editor<A>(app: &mut A) -> Cookie<'_>
+        where A: TestApp
+    {
+        login_user(app, "test_editor", "test_editor").await
+    }
+
+    /// Logs-in with test account of the admin role, returns associated cookie
+    pub async fn login_test_admin<A>(app: &mut A) -> Cookie<'_>
+        where A: TestApp
+    {
+        login_user(app, "test_admin", "test_admin").await
+    }
+
+
+    /// Registers a new user account, for tests which need a user of their own
+    pub async fn create_test_user<A>(mut app: &mut A, name: &str, password: &str)
+        where A: TestApp
+    {
+        // make registration request
+        let user_data = CreateUserRequestData {
+            name: name.to_string(),
+            password: password.to_string()
+        };
+        let req = test::TestRequest::post()
+            .set_json(&user_data)
+            .uri("/users")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+
+        // expect success
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+    }
+
+
+    /// Logs-in with an account, returns associated cookie
+    pub async fn login_user<A>(mut app: &mut A, name: &str, password: &str) -> Cookie<'static>
         where A: TestApp
     {
-        // create auth request for test user
+        // create auth request for user
         let auth_data = AuthRequestData {
-            name: "test_user".to_string(),
-            password: "test_user".to_string()
+            name: name.to_string(),
+            password: password.to_string()
         };
 
         // make login request
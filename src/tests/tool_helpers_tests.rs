@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use diesel::prelude::*;
+
+    use langis::app::models::{NewWordEntry, NewWordEntryGroup};
+    use langis::helpers::bulk_writer::BulkWriter;
+    use langis::helpers::tool_helpers::{self, UpstreamGroups};
+    use crate::tests::test_helpers::tests::test_db_pool;
+
+    /// imports keyed lines as the edict importer does, returning the group ids and digests by stored key
+    fn import_keyed_lines(conn: &PgConnection, source_id: i32, lines: &[(&str, &str)])
+        -> HashMap<String, (i32, Option<String>)>
+    {
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let mut writer = BulkWriter::new(conn);
+            let mut upstream_groups = UpstreamGroups::load(writer.conn(), source_id);
+            let unchanged_lines = lines.iter()
+                .map(|(upstream_key, upstream_hash)| upstream_groups.keep_unchanged(upstream_key, upstream_hash))
+                .collect::<Vec<_>>();
+            for ((upstream_key, upstream_hash), unchanged) in lines.iter().zip(unchanged_lines) {
+                if !unchanged {
+                    upstream_groups.upsert_keyed_group(&mut writer, upstream_key.to_string(), upstream_hash.to_string())?;
+                }
+            }
+            upstream_groups.finish(&mut writer);
+            writer.finish()
+        }).expect("Error importing keyed lines");
+        tool_helpers::load_upstream_groups(conn, source_id)
+    }
+
+    #[test]
+    fn test_upstream_groups_of_duplicate_keys() {
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let source = tool_helpers::find_or_create_source(conn, "upstream_groups_duplicates".to_string());
+        let hash = |value: &str| Some(value.to_string());
+
+        // a unique headword is stored by its key
+        let groups = import_keyed_lines(conn, source.id, &[("長", "length"), ("牌", "card")]);
+        assert_eq!(groups.len(), 2);
+        let length_id = groups["長"].0;
+        let card_id = groups["牌"].0;
+
+        // a second line of the headword is numbered, the first keeps its key and group
+        let groups = import_keyed_lines(conn, source.id, &[("長", "forever"), ("長", "length"), ("牌", "card")]);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups["長"], (length_id, hash("length")));
+        let forever_id = groups["長#2"].0;
+        assert_eq!(groups["長#2"].1, hash("forever"));
+
+        // reordering the lines keeps them unchanged
+        let groups = import_keyed_lines(conn, source.id, &[("牌", "card"), ("長", "length"), ("長", "forever")]);
+        assert_eq!(groups["長"], (length_id, hash("length")));
+        assert_eq!(groups["長#2"], (forever_id, hash("forever")));
+
+        // modifying one of the lines replaces its own group
+        let groups = import_keyed_lines(conn, source.id, &[("長", "length"), ("長", "always"), ("牌", "card")]);
+        assert_eq!(groups["長"], (length_id, hash("length")));
+        assert_eq!(groups["長#2"], (forever_id, hash("always")));
+
+        // removing a line deletes its group, the remaining line keeps its key and group
+        let groups = import_keyed_lines(conn, source.id, &[("長", "always"), ("牌", "card")]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["長#2"], (forever_id, hash("always")));
+        assert_eq!(groups["牌"], (card_id, hash("card")));
+
+        // a new line of the headword reuses the free key of the deleted group
+        let groups = import_keyed_lines(conn, source.id, &[("長", "always"), ("長", "length"), ("牌", "card")]);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups["長#2"], (forever_id, hash("always")));
+        assert_eq!(groups["長"].1, hash("length"));
+        assert_ne!(groups["長"].0, length_id);
+    }
+
+    #[test]
+    fn test_upstream_groups_of_identical_lines() {
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let source = tool_helpers::find_or_create_source(conn, "upstream_groups_identical".to_string());
+
+        // identical lines are numbered, and stay unchanged on re-import
+        let groups = import_keyed_lines(conn, source.id, &[("長", "length"), ("長", "length")]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(import_keyed_lines(conn, source.id, &[("長", "length"), ("長", "length")]), groups);
+
+        // removing one of them keeps the group with the lowest id
+        let kept_id = groups.values().map(|(group_id, _)| *group_id).min().unwrap();
+        let groups = import_keyed_lines(conn, source.id, &[("長", "long")]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups.values().next().unwrap().0, kept_id);
+    }
+
+    #[test]
+    fn test_update_reading_search_keys() {
+        use crate::schema::word_entry_readings;
+
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        let source = tool_helpers::find_or_create_source(conn, "update_search_keys".to_string());
+
+        // write a reading with a key computed by an older version of the importer
+        let word_entry_id = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let mut writer = BulkWriter::new(conn);
+            let group_id = writer.add_group(NewWordEntryGroup::from_source(source.id))?;
+            let word_entry_id = writer.add_entry(NewWordEntry {
+                orth: "東京".to_string(),
+                orth_lang: "jpn".to_string(),
+                quote: "Tokyo".to_string(),
+                quote_lang: "eng".to_string(),
+                sense: 0,
+                group_id,
+            })?;
+            writer.add_reading(word_entry_id, "とうきょう".to_string(), None, Some("tokyo".to_string()));
+            writer.finish()?;
+            Ok(word_entry_id)
+        }).expect("Error writing reading");
+
+        // expect only the outdated key to be recomputed
+        assert_eq!(tool_helpers::update_reading_search_keys(conn, "jpn"), 1);
+        let search_key = word_entry_readings::table
+            .filter(word_entry_readings::word_entry_id.eq(word_entry_id))
+            .select(word_entry_readings::search_key)
+            .first::<Option<String>>(conn)
+            .expect("Error loading updated reading");
+        assert_eq!(search_key.as_deref(), Some("toukyou"));
+
+        // expect the keys to be left alone on the next run
+        assert_eq!(tool_helpers::update_reading_search_keys(conn, "jpn"), 0);
+    }
+}
@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test::{call_service, TestRequest}};
+
+    use actix_http::cookie::Cookie;
+    use diesel::prelude::*;
+
+    use crate::tests::test_helpers::tests::{create_test_app,create_test_user,login_test_admin,login_user,test_db_pool,TestApp};
+    use crate::app::controllers::auth_controller::{AuthRequestData,ResetPasswordRequestData};
+    use crate::app::controllers::users_controller::{CreatePasswordResetResult,CreateUserRequestData,UpdateUserRoleData};
+    use crate::app::errors::{ErrorResponse, FieldError};
+    use crate::app::models::{Role, SlimUser};
+    use crate::app::security::hash_token;
+
+    /// gets the user of a session cookie
+    async fn get_me<A>(mut app: &mut A, cookie: &Cookie<'static>) -> SlimUser
+        where A: TestApp
+    {
+        let req = TestRequest::get()
+            .cookie(cookie.clone())
+            .uri("/auth")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        serde_json::from_slice(response_body)
+            .expect("Failed to parse SlimUser from GET /auth response")
+    }
+
+    #[actix_rt::test]
+    async fn test_create_user_and_login() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make registration request
+        let user_data = CreateUserRequestData {
+            name: "new_test_user".to_string(),
+            password: "new_password1".to_string()
+        };
+        let req = TestRequest::post()
+            .set_json(&user_data)
+            .uri("/users")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect created
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: SlimUser = serde_json::from_slice(response_body)
+            .expect("Failed to parse SlimUser from POST /users response");
+        assert_eq!(parsed_json.name, "new_test_user");
+
+        // expect new user can login
+        let auth_data = AuthRequestData {
+            name: "new_test_user".to_string(),
+            password: "new_password1".to_string()
+        };
+        let req2 = TestRequest::post()
+            .set_json(&auth_data)
+            .uri("/auth")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_create_user_name_taken() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make registration request with fixture user name
+        let user_data = CreateUserRequestData {
+            name: "test_user".to_string(),
+            password: "new_password1".to_string()
+        };
+        let req = TestRequest::post()
+            .set_json(&user_data)
+            .uri("/users")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect bad request
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // expect the error to be reported against the name field
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ErrorResponse = serde_json::from_slice(response_body)
+            .expect("Failed to parse ErrorResponse from response");
+        assert_eq!(parsed_json.code, "validation_failed");
+        let field_errors: Vec<FieldError> = serde_json::from_value(parsed_json.details.unwrap())
+            .expect("Failed to parse FieldError list from details");
+        assert_eq!(field_errors, vec![FieldError {
+            field: "name".to_string(),
+            message: "is already taken".to_string(),
+        }]);
+    }
+
+    #[actix_rt::test]
+    async fn test_create_user_invalid_details() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // invalid name, weak password, short password
+        let invalid_details = vec![
+            ("bad name!", "new_password1"),
+            ("weak_password_user", "password"),
+            ("short_password_user", "pw1"),
+        ];
+        for (name, password) in invalid_details {
+            let user_data = CreateUserRequestData {
+                name: name.to_string(),
+                password: password.to_string()
+            };
+            let req = TestRequest::post()
+                .set_json(&user_data)
+                .uri("/users")
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+
+            // expect bad request
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_update_user_role() {
+        // setup test app
+        let mut app = create_test_app().await;
+        let admin_cookie = login_test_admin(&mut app).await.into_owned();
+
+        // new users start as readers
+        create_test_user(&mut app, "role_user", "role_password1").await;
+        let cookie = login_user(&mut app, "role_user", "role_password1").await;
+        let user = get_me(&mut app, &cookie).await;
+        assert_eq!(user.role, Role::Reader);
+
+        // expect non-admins cannot change roles
+        let req = TestRequest::put()
+            .cookie(cookie.clone())
+            .set_json(&UpdateUserRoleData { role: Role::Admin })
+            .uri(&format!("/users/{}/role", user.id))
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+        // promote user as admin
+        let req2 = TestRequest::put()
+            .cookie(admin_cookie.clone())
+            .set_json(&UpdateUserRoleData { role: Role::Editor })
+            .uri(&format!("/users/{}/role", user.id))
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+
+        // expect role to apply to the existing session
+        let user = get_me(&mut app, &cookie).await;
+        assert_eq!(user.role, Role::Editor);
+
+        // expect admins cannot change their own role
+        let admin = get_me(&mut app, &admin_cookie).await;
+        let req3 = TestRequest::put()
+            .cookie(admin_cookie.clone())
+            .set_json(&UpdateUserRoleData { role: Role::Reader })
+            .uri(&format!("/users/{}/role", admin.id))
+            .to_request();
+        let resp3 = call_service(&mut app, req3).await;
+        assert_eq!(resp3.status(), http::StatusCode::BAD_REQUEST);
+
+        // expect not found for missing users
+        let req4 = TestRequest::put()
+            .cookie(admin_cookie.clone())
+            .set_json(&UpdateUserRoleData { role: Role::Editor })
+            .uri("/users/0/role")
+            .to_request();
+        let resp4 = call_service(&mut app, req4).await;
+        assert_eq!(resp4.status(), http::StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn test_admin_password_reset() {
+        // setup test app
+        let mut app = create_test_app().await;
+        let admin_cookie = login_test_admin(&mut app).await.into_owned();
+
+        create_test_user(&mut app, "admin_reset_user", "admin_reset_password1").await;
+        let cookie = login_user(&mut app, "admin_reset_user", "admin_reset_password1").await;
+        let user = get_me(&mut app, &cookie).await;
+
+        // expect non-admins cannot issue reset tokens
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .uri(&format!("/users/{}/password_reset", user.id))
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+        // issue reset token as admin
+        let req2 = TestRequest::post()
+            .cookie(admin_cookie.clone())
+            .uri(&format!("/users/{}/password_reset", user.id))
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::CREATED);
+        let response_body = match resp2.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let created: CreatePasswordResetResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse CreatePasswordResetResult from response");
+        assert_eq!(created.password_reset.user_id, user.id);
+
+        // expect only the hash of the token to be stored
+        {
+            use crate::schema::password_resets::dsl::{password_resets, token_hash, user_id};
+            let conn: &PgConnection = &test_db_pool().get().unwrap();
+            let stored_hash = password_resets.select(token_hash).filter(user_id.eq(user.id))
+                .first::<String>(conn).expect("Error finding password reset");
+            assert_eq!(stored_hash, hash_token(&created.token));
+        }
+
+        // redeem reset token
+        let req3 = TestRequest::post()
+            .set_json(&ResetPasswordRequestData {
+                token: created.token,
+                new_password: "admin_reset_password2".to_string(),
+            })
+            .uri("/auth/password_reset")
+            .to_request();
+        let resp3 = call_service(&mut app, req3).await;
+        assert_eq!(resp3.status(), http::StatusCode::OK);
+
+        // expect login with the new password
+        let req4 = TestRequest::post()
+            .set_json(&AuthRequestData {
+                name: "admin_reset_user".to_string(),
+                password: "admin_reset_password1".to_string(),
+            })
+            .uri("/auth")
+            .to_request();
+        assert_eq!(call_service(&mut app, req4).await.status(), http::StatusCode::UNAUTHORIZED);
+        login_user(&mut app, "admin_reset_user", "admin_reset_password2").await;
+    }
+}
@@ -2,8 +2,29 @@
 mod tests {
     use actix_web::{http, test::{call_service, TestRequest}};
 
-    use crate::tests::test_helpers::tests::{create_test_app};
-    use crate::app::controllers::word_entries_controller::{ListWordEntriesResult};
+    use crate::tests::test_helpers::tests::{create_test_app,create_test_user,login_test_editor,login_test_user,login_user,test_group_id,test_source_id,TestApp};
+    use crate::app::controllers::word_entries_controller::{CreateWordEntryData,
+        ListWordEntriesResult,ListWordEntriesResultRecord,TagFacet,UpdateWordEntryData,WordEntryReadingData};
+    use crate::app::errors::{ErrorResponse,FieldError};
+
+    /// Lists word entries, returns the records of the first page
+    async fn list_records<A>(mut app: &mut A, uri: &str) -> Vec<ListWordEntriesResultRecord>
+        where A: TestApp
+    {
+        let req = TestRequest::get()
+            .uri(uri)
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK, "Unexpected status for {}", uri);
+
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse ListWordEntriesResult from response");
+        parsed_json.page
+    }
 
     #[actix_rt::test]
     async fn test_list_word_entries() {
@@ -43,4 +64,799 @@ mod tests {
         assert_eq!(parsed_json.page[0].word_entry_tags.len(), 1);
         assert_eq!(parsed_json.page[0].word_entry_tags[0].tag, "test tag");
     }
-}
\ No newline at end of file
+
+    /// makes a list request, returning the parsed result
+    async fn list_word_entries<A>(mut app: &mut A, uri: &str) -> ListWordEntriesResult
+        where A: TestApp
+    {
+        let req = TestRequest::get()
+            .uri(uri)
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK, "Unexpected status for {}", uri);
+
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        serde_json::from_slice(response_body)
+            .expect("Failed to parse ListWordEntriesResult from response")
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_cursor_paging() {
+        // setup test app
+        let mut app = create_test_app().await;
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // create entries to page through
+        for i in 0..5 {
+            let entry_data = CreateWordEntryData {
+                orth: format!("cursor_orth_{}", i),
+                orth_lang: "test".to_string(),
+                quote: format!("cursor quote {}", i),
+                quote_lang: "test".to_string(),
+                sense: 0,
+                group_id: test_group_id(),
+                notes: vec![],
+                readings: vec![],
+                tags: vec![],
+            };
+            let req = TestRequest::post()
+                .cookie(cookie.clone())
+                .set_json(&entry_data)
+                .uri("/word_entries")
+                .to_request();
+            assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::CREATED);
+        }
+
+        for mode in &["like", "fuzzy"] {
+            let query = if *mode == "like" { "cursor_orth_%25" } else { "cursor_orth" };
+
+            // expect the full list in a single page
+            let full_uri = format!("/word_entries?query={}&mode={}&per_page=100", query, mode);
+            let full = list_word_entries(&mut app, &full_uri).await;
+            assert_eq!(full.total_count, Some(5));
+            assert_eq!(full.page_count, Some(1));
+            assert_eq!(full.next_cursor, None);
+            let full_ids = full.page.iter().map(|e| e.word_entry.id).collect::<Vec<_>>();
+
+            // follow cursors two results at a time
+            let first_uri = format!("/word_entries?query={}&mode={}&per_page=2", query, mode);
+            let mut result = list_word_entries(&mut app, &first_uri).await;
+            assert_eq!(result.page_count, Some(3));
+            let mut paged_ids = result.page.iter().map(|e| e.word_entry.id).collect::<Vec<_>>();
+            while let Some(cursor) = result.next_cursor {
+                let uri = format!("{}&cursor={}", first_uri, cursor);
+                result = list_word_entries(&mut app, &uri).await;
+                // counting is skipped by default for cursor pages
+                assert_eq!(result.total_count, None);
+                paged_ids.extend(result.page.iter().map(|e| e.word_entry.id));
+            }
+
+            // expect the same results in the same order
+            assert_eq!(paged_ids, full_ids, "Cursor paging mismatch for mode {}", mode);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_invalid_paging() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        let invalid_cases = vec![
+            "/word_entries?query=test_orth&per_page=0",
+            "/word_entries?query=test_orth&per_page=101",
+            "/word_entries?query=test_orth&cursor=not_a_cursor",
+            // unranked cursor for a ranked mode
+            "/word_entries?query=test_orth&mode=fuzzy&cursor=1",
+            "/word_entry_groups?query=test_orth&cursor=1",
+        ];
+        for uri in invalid_cases {
+            let req = TestRequest::get()
+                .uri(uri)
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST, "Unexpected status for {}", uri);
+
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ErrorResponse = serde_json::from_slice(response_body)
+                .expect("Failed to parse ErrorResponse from response");
+            assert_eq!(parsed_json.code, "validation_failed");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_invalid_params() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        let invalid_cases = vec![
+            ("/word_entries?query=test_orth&page=0", "page"),
+            ("/word_entries?query=test_orth&page=-3", "page"),
+            ("/word_entries?page=1", "query"),
+            ("/word_entries?query=%20&page=1", "query"),
+            ("/word_entries?query=test_orth&page=first", "query_string"),
+            ("/word_entry_groups?query=test_orth&page=0", "page"),
+            ("/word_entry_groups?page=1", "query"),
+        ];
+        for (uri, field) in invalid_cases {
+            let req = TestRequest::get()
+                .uri(uri)
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST, "Unexpected status for {}", uri);
+
+            // expect the invalid field to be named in the details
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ErrorResponse = serde_json::from_slice(response_body)
+                .expect("Failed to parse ErrorResponse from response");
+            assert_eq!(parsed_json.code, "validation_failed");
+            let field_errors: Vec<FieldError> = serde_json::from_value(parsed_json.details.unwrap())
+                .expect("Failed to parse FieldErrors from details");
+            assert_eq!(field_errors[0].field, field, "Unexpected field for {}", uri);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_filters() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // expected result counts for filter combinations
+        let filter_cases = vec![
+            ("orth_lang=test&quote_lang=test".to_string(), 1),
+            ("orth_lang=jpn".to_string(), 0),
+            ("quote_lang=eng".to_string(), 0),
+            (format!("source_id={}", test_source_id()), 1),
+            (format!("source_id={}", test_source_id() + 1), 0),
+        ];
+        for (filter, expected_len) in filter_cases {
+            // make request
+            let req = TestRequest::get()
+                .uri(&format!("/word_entries?query=test_orth&page=1&{}", filter))
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+
+            // expect success
+            assert_eq!(resp.status(), http::StatusCode::OK);
+
+            // parse json from response
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+                .expect("Failed to parse ListWordEntriesResult from response");
+
+            // expect filtered results
+            assert_eq!(parsed_json.page.len(), expected_len, "filter: {}", filter);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_ranked_modes() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // misspelled fuzzy query, and a fulltext query for words in any order
+        let mode_cases = vec![
+            "mode=fuzzy&query=test_orht",
+            "mode=fulltext&query=quote%20test",
+        ];
+        for mode_case in mode_cases {
+            // make request
+            let req = TestRequest::get()
+                .uri(&format!("/word_entries?page=1&{}", mode_case))
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+
+            // expect success
+            assert_eq!(resp.status(), http::StatusCode::OK);
+
+            // parse json from response
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+                .expect("Failed to parse ListWordEntriesResult from response");
+
+            // expect test entry ranked with a score
+            assert!(!parsed_json.page.is_empty(), "mode: {}", mode_case);
+            assert_eq!(parsed_json.page[0].word_entry.orth, "test_orth", "mode: {}", mode_case);
+            assert!(parsed_json.page[0].score.unwrap_or(0.0) > 0.0, "mode: {}", mode_case);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_by_reading() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make request
+        let req = TestRequest::get()
+            .uri("/word_entries?mode=reading&query=test%20reading&page=1")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect success
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse ListWordEntriesResult from response");
+
+        // expect test entry with its reading marked as matched
+        assert_eq!(parsed_json.page.len(), 1);
+        assert_eq!(parsed_json.page[0].word_entry.orth, "test_orth");
+        assert_eq!(parsed_json.page[0].matched_reading_ids,
+            vec![parsed_json.page[0].word_entry_readings[0].id]);
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_match_modes() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login test user
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // create entries whose orths match the same raw LIKE pattern
+        let entries = vec![
+            ("100%_sure", "literal quote", "literal note", "literal reading"),
+            ("1000 sure", "wildcard quote", "wildcard note", "wildcard reading"),
+        ];
+        for (orth, quote, note, reading) in entries {
+            let entry_data = CreateWordEntryData {
+                orth: orth.to_string(),
+                orth_lang: "mtch".to_string(),
+                quote: quote.to_string(),
+                quote_lang: "test".to_string(),
+                sense: 0,
+                group_id: test_group_id(),
+                notes: vec![note.to_string()],
+                readings: vec![WordEntryReadingData { reading: reading.to_string(), reading_tag: None }],
+                tags: vec![],
+            };
+            let req = TestRequest::post()
+                .cookie(cookie.clone())
+                .set_json(&entry_data)
+                .uri("/word_entries")
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::CREATED);
+        }
+
+        // expect metacharacters to be matched literally with a match mode
+        let cases = vec![
+            ("query=100%25_sure", vec!["100%_sure", "1000 sure"]),
+            ("query=100%25_sure&match=exact", vec!["100%_sure"]),
+            ("query=100%25_SURE&match=exact", vec!["100%_sure"]),
+            ("query=100%25&match=prefix", vec!["100%_sure"]),
+            ("query=_sure&match=suffix", vec!["100%_sure"]),
+            ("query=%25_&match=contains", vec!["100%_sure"]),
+            ("query=sure&match=contains", vec!["100%_sure", "1000 sure"]),
+            ("query=quote&match=suffix&field=quote", vec!["100%_sure", "1000 sure"]),
+            ("query=wildcard&match=prefix&field=quote", vec!["1000 sure"]),
+            ("query=quote&match=suffix&field=orth", vec![]),
+            ("query=literal%20note&match=exact&field=note", vec!["100%_sure"]),
+            ("query=wildcard%20read&match=prefix&field=reading", vec!["1000 sure"]),
+            ("query=literal%20reading&match=exact&mode=reading", vec!["100%_sure"]),
+        ];
+        for (params, expected_orths) in cases {
+            let uri = format!("/word_entries?orth_lang=mtch&{}", params);
+            let records = list_records(&mut app, &uri).await;
+            let orths = records.iter().map(|r| r.word_entry.orth.as_str()).collect::<Vec<_>>();
+            assert_eq!(orths, expected_orths, "Unexpected results for {}", uri);
+        }
+
+        // expect readings matched through the reading field to be marked
+        let records = list_records(&mut app,
+            "/word_entries?orth_lang=mtch&query=literal&match=prefix&field=reading").await;
+        assert_eq!(records[0].matched_reading_ids, vec![records[0].word_entry_readings[0].id]);
+
+        // expect match and field to be rejected for modes they do not apply to
+        let invalid_cases = vec![
+            ("query=sure&mode=fuzzy&match=exact", "match"),
+            ("query=sure&mode=reading&field=orth", "field"),
+            ("query=sure&mode=fulltext&field=quote", "field"),
+            ("query=sure&match=anywhere", "query_string"),
+        ];
+        for (params, field) in invalid_cases {
+            let req = TestRequest::get()
+                .uri(&format!("/word_entries?{}", params))
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST, "Unexpected status for {}", params);
+
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ErrorResponse = serde_json::from_slice(response_body)
+                .expect("Failed to parse ErrorResponse from response");
+            let field_errors: Vec<FieldError> = serde_json::from_value(parsed_json.details.unwrap())
+                .expect("Failed to parse FieldErrors from details");
+            assert_eq!(field_errors[0].field, field, "Unexpected field for {}", params);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_by_tags() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login test user
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // create entries with overlapping tags
+        let entries = vec![
+            ("tag_filter_a", vec!["v5k", "vt"]),
+            ("tag_filter_b", vec!["v5k", "arch"]),
+            ("tag_filter_c", vec!["adj-i"]),
+        ];
+        for (orth, tags) in entries {
+            let entry_data = CreateWordEntryData {
+                orth: orth.to_string(),
+                orth_lang: "tagf".to_string(),
+                quote: "tag filter quote".to_string(),
+                quote_lang: "test".to_string(),
+                sense: 0,
+                group_id: test_group_id(),
+                notes: vec![],
+                readings: vec![],
+                tags: tags.into_iter().map(|tag| tag.to_string()).collect(),
+            };
+            let req = TestRequest::post()
+                .cookie(cookie.clone())
+                .set_json(&entry_data)
+                .uri("/word_entries")
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::CREATED);
+        }
+
+        // expect results to have every included tag and none of the excluded
+        let cases = vec![
+            ("", vec!["tag_filter_a", "tag_filter_b", "tag_filter_c"],
+                vec![("v5k", 2), ("adj-i", 1), ("arch", 1), ("vt", 1)]),
+            ("&tags=v5k", vec!["tag_filter_a", "tag_filter_b"],
+                vec![("v5k", 2), ("arch", 1), ("vt", 1)]),
+            ("&tags=v5k,vt", vec!["tag_filter_a"],
+                vec![("v5k", 1), ("vt", 1)]),
+            ("&exclude_tags=arch", vec!["tag_filter_a", "tag_filter_c"],
+                vec![("adj-i", 1), ("v5k", 1), ("vt", 1)]),
+            ("&tags=v5k&exclude_tags=arch,adj-i", vec!["tag_filter_a"],
+                vec![("v5k", 1), ("vt", 1)]),
+        ];
+        for (params, expected_orths, expected_facets) in cases {
+            let req = TestRequest::get()
+                .uri(&format!("/word_entries?query=tag_filter%25&orth_lang=tagf{}", params))
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::OK);
+
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+                .expect("Failed to parse ListWordEntriesResult from response");
+            let orths = parsed_json.page.iter().map(|r| r.word_entry.orth.as_str()).collect::<Vec<_>>();
+            assert_eq!(orths, expected_orths, "Unexpected results for {}", params);
+
+            // expect tag facets of the filtered results, most frequent first
+            let expected_facets = expected_facets.into_iter()
+                .map(|(tag, count)| TagFacet { tag: tag.to_string(), count })
+                .collect::<Vec<_>>();
+            assert_eq!(parsed_json.tag_facets, Some(expected_facets), "Unexpected facets for {}", params);
+        }
+
+        // expect facets to be skipped when not requested
+        let req = TestRequest::get()
+            .uri("/word_entries?query=tag_filter%25&orth_lang=tagf&facets=false")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse ListWordEntriesResult from response");
+        assert_eq!(parsed_json.tag_facets, None);
+
+        // expect blank tags to be rejected
+        let req = TestRequest::get()
+            .uri("/word_entries?query=tag_filter%25&tags=v5k,")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_by_pinyin_reading() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login test user
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // create cedict-style entry with tone-number reading
+        let entry_data = CreateWordEntryData {
+            orth: "大牌檔".to_string(),
+            orth_lang: "zho".to_string(),
+            quote: "food stall".to_string(),
+            quote_lang: "eng".to_string(),
+            sense: 0,
+            group_id: test_group_id(),
+            notes: vec![],
+            readings: vec![WordEntryReadingData { reading: "da4 pai2 dang4".to_string(), reading_tag: None }],
+            tags: vec![],
+        };
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+
+        // expect tone-mark, toneless and compact tone-number queries to match
+        let queries = vec!["d%C3%A0p%C3%A1id%C3%A0ng", "dapaidang", "da4pai2dang4"];
+        for query in queries {
+            let req = TestRequest::get()
+                .uri(&format!("/word_entries?mode=reading&page=1&orth_lang=zho&query={}", query))
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::OK);
+
+            // parse json from response
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+                .expect("Failed to parse ListWordEntriesResult from response");
+
+            assert_eq!(parsed_json.page.len(), 1, "query: {}", query);
+            assert_eq!(parsed_json.page[0].word_entry.orth, "大牌檔");
+            assert_eq!(parsed_json.page[0].matched_reading_ids.len(), 1);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entries_by_romaji_reading() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login test user
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // create edict-style entry with kana reading
+        let entry_data = CreateWordEntryData {
+            orth: "食べる".to_string(),
+            orth_lang: "jpn".to_string(),
+            quote: "to eat".to_string(),
+            quote_lang: "eng".to_string(),
+            sense: 0,
+            group_id: test_group_id(),
+            notes: vec![],
+            readings: vec![WordEntryReadingData { reading: "たべる".to_string(), reading_tag: None }],
+            tags: vec![],
+        };
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+
+        // expect romaji, hiragana and katakana queries to match
+        let queries = vec!["taberu", "%E3%81%9F%E3%81%B9%E3%82%8B", "%E3%82%BF%E3%83%99%E3%83%AB"];
+        for query in queries {
+            let req = TestRequest::get()
+                .uri(&format!("/word_entries?mode=reading&page=1&orth_lang=jpn&query={}", query))
+                .to_request();
+            let resp = call_service(&mut app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::OK);
+
+            // parse json from response
+            let response_body = match resp.response().body().as_ref() {
+                Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+                _ => panic!("Response error"),
+            };
+            let parsed_json: ListWordEntriesResult = serde_json::from_slice(response_body)
+                .expect("Failed to parse ListWordEntriesResult from response");
+
+            assert_eq!(parsed_json.page.len(), 1, "query: {}", query);
+            assert_eq!(parsed_json.page[0].word_entry.orth, "食べる");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_create_word_entry_unauthorized() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make request without session cookie
+        let entry_data = CreateWordEntryData {
+            orth: "crud_unauthorized".to_string(),
+            orth_lang: "test".to_string(),
+            quote: "unauthorized quote".to_string(),
+            quote_lang: "test".to_string(),
+            sense: 0,
+            group_id: test_group_id(),
+            notes: vec![],
+            readings: vec![],
+            tags: vec![],
+        };
+        let req = TestRequest::post()
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect UNAUTHORIZED 401 response
+        assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn test_create_word_entry_as_reader() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login as a new user with the default reader role
+        create_test_user(&mut app, "reader_user", "reader_password1").await;
+        let cookie = login_user(&mut app, "reader_user", "reader_password1").await;
+
+        // make create request
+        let entry_data = CreateWordEntryData {
+            orth: "crud_reader".to_string(),
+            orth_lang: "test".to_string(),
+            quote: "reader quote".to_string(),
+            quote_lang: "test".to_string(),
+            sense: 0,
+            group_id: test_group_id(),
+            notes: vec![],
+            readings: vec![],
+            tags: vec![],
+        };
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect FORBIDDEN 403 response
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+        // expect readers can still view entries
+        let req2 = TestRequest::get()
+            .cookie(cookie.clone())
+            .uri("/word_entries?query=test_orth&page=1")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_get_missing_word_entry() {
+        // setup test app
+        let mut app = create_test_app().await;
+        let cookie = login_test_user(&mut app).await;
+
+        // make request for an id that does not exist
+        let req = TestRequest::get()
+            .cookie(cookie.clone())
+            .uri("/word_entries/0")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ErrorResponse = serde_json::from_slice(response_body)
+            .expect("Failed to parse ErrorResponse from response");
+        assert_eq!(parsed_json.code, "not_found");
+        assert!(parsed_json.request_id.is_some());
+    }
+
+    #[actix_rt::test]
+    async fn test_create_update_delete_word_entry() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login test user
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // make create request
+        let entry_data = CreateWordEntryData {
+            orth: "crud_orth".to_string(),
+            orth_lang: "test".to_string(),
+            quote: "crud quote".to_string(),
+            quote_lang: "test".to_string(),
+            sense: 0,
+            group_id: test_group_id(),
+            notes: vec!["crud note".to_string()],
+            readings: vec![WordEntryReadingData { reading: "crud reading".to_string(), reading_tag: None }],
+            tags: vec!["crud tag".to_string(), "other tag".to_string()],
+        };
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect created
+        assert_eq!(resp.status(), http::StatusCode::CREATED);
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let created: ListWordEntriesResultRecord = serde_json::from_slice(response_body)
+            .expect("Failed to parse ListWordEntriesResultRecord from response");
+        let entry_id = created.word_entry.id;
+        assert_eq!(created.word_entry.orth, "crud_orth");
+        assert_eq!(created.word_entry_notes[0].note, "crud note");
+        assert_eq!(created.word_entry_readings[0].reading, "crud reading");
+        assert_eq!(created.word_entry_tags.len(), 2);
+
+        // make update request, replacing quote and tags
+        let update_data: UpdateWordEntryData = serde_json::from_value(serde_json::json!({
+            "quote": "updated crud quote",
+            "tags": ["updated tag"],
+        })).unwrap();
+        let req2 = TestRequest::patch()
+            .cookie(cookie.clone())
+            .set_json(&update_data)
+            .uri(&format!("/word_entries/{}", entry_id))
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+
+        // expect updated details, with untouched notes and readings
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+        let response_body2 = match resp2.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let updated: ListWordEntriesResultRecord = serde_json::from_slice(response_body2)
+            .expect("Failed to parse ListWordEntriesResultRecord from response");
+        assert_eq!(updated.word_entry.orth, "crud_orth");
+        assert_eq!(updated.word_entry.quote, "updated crud quote");
+        assert_eq!(updated.word_entry_notes.len(), 1);
+        assert_eq!(updated.word_entry_readings.len(), 1);
+        assert_eq!(updated.word_entry_tags.len(), 1);
+        assert_eq!(updated.word_entry_tags[0].tag, "updated tag");
+
+        // make delete request
+        let req3 = TestRequest::delete()
+            .cookie(cookie.clone())
+            .uri(&format!("/word_entries/{}", entry_id))
+            .to_request();
+        let resp3 = call_service(&mut app, req3).await;
+        assert_eq!(resp3.status(), http::StatusCode::OK);
+
+        // expect NOT_FOUND once deleted
+        let req4 = TestRequest::get()
+            .cookie(cookie.clone())
+            .uri(&format!("/word_entries/{}", entry_id))
+            .to_request();
+        let resp4 = call_service(&mut app, req4).await;
+        assert_eq!(resp4.status(), http::StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn test_create_word_entry_missing_group() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login test user
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // make create request with unknown group
+        let entry_data = CreateWordEntryData {
+            orth: "crud_missing_group".to_string(),
+            orth_lang: "test".to_string(),
+            quote: "missing group quote".to_string(),
+            quote_lang: "test".to_string(),
+            sense: 0,
+            group_id: -1,
+            notes: vec![],
+            readings: vec![],
+            tags: vec![],
+        };
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+
+        // expect bad request
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn test_create_word_entry_invalid_fields() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // login test user
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // make create request with several invalid fields
+        let entry_data = CreateWordEntryData {
+            orth: " ".to_string(),
+            orth_lang: "test".to_string(),
+            quote: "invalid fields quote".to_string(),
+            quote_lang: "test".to_string(),
+            sense: -1,
+            group_id: test_group_id(),
+            notes: vec!["valid note".to_string(), "".to_string()],
+            readings: vec![],
+            tags: vec![],
+        };
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // expect every invalid field to be reported
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ErrorResponse = serde_json::from_slice(response_body)
+            .expect("Failed to parse ErrorResponse from response");
+        assert_eq!(parsed_json.code, "validation_failed");
+        let field_errors: Vec<FieldError> = serde_json::from_value(parsed_json.details.unwrap())
+            .expect("Failed to parse FieldErrors from details");
+        let fields = field_errors.iter().map(|e| e.field.as_str()).collect::<Vec<_>>();
+        assert_eq!(fields, vec!["orth", "sense", "notes[1]"]);
+
+        // make create request missing a required field
+        let req2 = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&serde_json::json!({
+                "orth": "missing quote",
+                "orth_lang": "test",
+                "quote_lang": "test",
+                "group_id": test_group_id(),
+            }))
+            .uri("/word_entries")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::BAD_REQUEST);
+
+        // expect the missing field to be named
+        let response_body2 = match resp2.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json2: ErrorResponse = serde_json::from_slice(response_body2)
+            .expect("Failed to parse ErrorResponse from response");
+        let field_errors2: Vec<FieldError> = serde_json::from_value(parsed_json2.details.unwrap())
+            .expect("Failed to parse FieldErrors from details");
+        assert_eq!(field_errors2, vec![FieldError { field: "quote".to_string(), message: "is required".to_string() }]);
+    }
+}
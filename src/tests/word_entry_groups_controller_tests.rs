@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use actix_http::cookie::Cookie;
+    use actix_web::{http, test::{call_service, TestRequest}};
+    use diesel::prelude::*;
+
+    use crate::tests::test_helpers::tests::{create_test_app,login_test_editor,test_db_pool,test_source_id,TestApp};
+    use crate::app::controllers::word_entries_controller::{CreateWordEntryData,TagFacet,WordEntryReadingData};
+    use crate::app::controllers::word_entry_groups_controller::{ListWordEntryGroupsResult,WordEntryGroupResult};
+    use crate::app::models::{NewWordEntryGroup, WordEntryGroup};
+
+    /// inserts an empty word_entry_groups record in the test source
+    fn insert_test_group() -> i32 {
+        use crate::schema::word_entry_groups;
+
+        let conn: &PgConnection = &test_db_pool().get().unwrap();
+        diesel::insert_into(word_entry_groups::table)
+            .values(&NewWordEntryGroup::from_source(test_source_id()))
+            .get_result::<WordEntryGroup>(conn)
+            .expect("Error inserting word entry group")
+            .id
+    }
+
+    /// creates a word entry with a single reading and the given tags in a group
+    async fn create_group_entry<A>(mut app: &mut A, cookie: &Cookie<'static>, group_id: i32, sense: i32, orth: &str,
+        tags: &[&str]) where A: TestApp
+    {
+        let entry_data = CreateWordEntryData {
+            orth: orth.to_string(),
+            orth_lang: "test".to_string(),
+            quote: format!("{} quote", orth),
+            quote_lang: "test".to_string(),
+            sense,
+            group_id,
+            notes: vec![],
+            readings: vec![WordEntryReadingData {
+                reading: format!("{} reading", orth),
+                reading_tag: None,
+            }],
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        };
+        let req = TestRequest::post()
+            .cookie(cookie.clone())
+            .set_json(&entry_data)
+            .uri("/word_entries")
+            .to_request();
+        assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::CREATED);
+    }
+
+    #[actix_rt::test]
+    async fn test_list_word_entry_groups() {
+        // setup test app
+        let mut app = create_test_app().await;
+        let cookie = login_test_editor(&mut app).await.into_owned();
+
+        // one group with two matching senses and a non-matching orth variant, one with a single match
+        let first_group_id = insert_test_group();
+        create_group_entry(&mut app, &cookie, first_group_id, 2, "grouped_hit", &["grouped_tag"]).await;
+        create_group_entry(&mut app, &cookie, first_group_id, 1, "grouped_hit", &["grouped_tag"]).await;
+        create_group_entry(&mut app, &cookie, first_group_id, 1, "grouped_miss", &[]).await;
+        let second_group_id = insert_test_group();
+        create_group_entry(&mut app, &cookie, second_group_id, 1, "grouped_hit_too", &["grouped_tag"]).await;
+
+        // make request
+        let req = TestRequest::get()
+            .uri("/word_entry_groups?query=grouped_hit%25&page=1")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: ListWordEntryGroupsResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse ListWordEntryGroupsResult from response");
+
+        // expect one result per group, with only the matching senses ordered by sense
+        assert_eq!(parsed_json.page_count, Some(1));
+        assert_eq!(parsed_json.page.len(), 2);
+        assert_eq!(parsed_json.page[0].word_entry_group.id, first_group_id);
+        let first_senses = parsed_json.page[0].word_entries.iter()
+            .map(|e| (e.word_entry.sense, e.word_entry.orth.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(first_senses, vec![(1, "grouped_hit"), (2, "grouped_hit")]);
+        assert_eq!(parsed_json.page[0].word_entries[0].word_entry_readings.len(), 1);
+        assert_eq!(parsed_json.page[1].word_entry_group.id, second_group_id);
+        assert_eq!(parsed_json.page[1].word_entries.len(), 1);
+
+        // expect tag facets to count groups, not their entries
+        assert_eq!(parsed_json.tag_facets, Some(vec![TagFacet { tag: "grouped_tag".to_string(), count: 2 }]));
+
+        // expect ranked modes to score groups by their best entry
+        let req2 = TestRequest::get()
+            .uri("/word_entry_groups?query=grouped_hit_too&page=1&mode=fuzzy")
+            .to_request();
+        let resp2 = call_service(&mut app, req2).await;
+        assert_eq!(resp2.status(), http::StatusCode::OK);
+        let response_body2 = match resp2.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json2: ListWordEntryGroupsResult = serde_json::from_slice(response_body2)
+            .expect("Failed to parse ListWordEntryGroupsResult from response");
+        assert_eq!(parsed_json2.page[0].word_entry_group.id, second_group_id);
+        let best_score = parsed_json2.page[0].score.expect("Missing group score");
+        assert!(parsed_json2.page[0].word_entries.iter().all(|e| e.score.unwrap() <= best_score));
+    }
+
+    #[actix_rt::test]
+    async fn test_get_word_entry_group() {
+        // setup test app
+        let mut app = create_test_app().await;
+        let cookie = login_test_editor(&mut app).await.into_owned();
+        let group_id = insert_test_group();
+
+        // add senses out of order, with two orth variants of the first sense
+        let senses = vec![(2, "group_orth"), (1, "group_orth"), (1, "group_orth_variant")];
+        for (sense, orth) in senses {
+            let entry_data = CreateWordEntryData {
+                orth: orth.to_string(),
+                orth_lang: "test".to_string(),
+                quote: format!("group quote {}", sense),
+                quote_lang: "test".to_string(),
+                sense,
+                group_id,
+                notes: vec![format!("group note {}", sense)],
+                readings: vec![WordEntryReadingData {
+                    reading: "group reading".to_string(),
+                    reading_tag: None,
+                }],
+                tags: vec!["group_tag".to_string()],
+            };
+            let req = TestRequest::post()
+                .cookie(cookie.clone())
+                .set_json(&entry_data)
+                .uri("/word_entries")
+                .to_request();
+            assert_eq!(call_service(&mut app, req).await.status(), http::StatusCode::CREATED);
+        }
+
+        // make request, groups can be read without signing in
+        let req = TestRequest::get()
+            .uri(&format!("/word_entry_groups/{}", group_id))
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // parse json from response
+        let response_body = match resp.response().body().as_ref() {
+            Some(actix_web::body::Body::Bytes(bytes)) => bytes,
+            _ => panic!("Response error"),
+        };
+        let parsed_json: WordEntryGroupResult = serde_json::from_slice(response_body)
+            .expect("Failed to parse WordEntryGroupResult from response");
+
+        // expect source and all senses ordered by sense, with joined records
+        assert_eq!(parsed_json.word_entry_group.id, group_id);
+        assert_eq!(parsed_json.source.name, "test_source");
+        let entries = parsed_json.word_entries.iter()
+            .map(|e| (e.word_entry.sense, e.word_entry.orth.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(entries, vec![(1, "group_orth"), (1, "group_orth_variant"), (2, "group_orth")]);
+        for entry in parsed_json.word_entries.iter() {
+            assert_eq!(entry.word_entry_notes.len(), 1);
+            assert_eq!(entry.word_entry_readings.len(), 1);
+            assert_eq!(entry.word_entry_tags.len(), 1);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_get_missing_word_entry_group() {
+        // setup test app
+        let mut app = create_test_app().await;
+
+        // make request for an id that does not exist
+        let req = TestRequest::get()
+            .uri("/word_entry_groups/0")
+            .to_request();
+        let resp = call_service(&mut app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+    }
+}
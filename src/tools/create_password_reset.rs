@@ -0,0 +1,33 @@
+extern crate diesel;
+
+use std::env;
+use diesel::PgConnection;
+
+use langis::app::database;
+use langis::helpers::tool_helpers;
+
+/// main
+fn main() {
+    // get user name from command line argument
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        println!("Usage: `create-password-reset [user name]`");
+        println!("  user name must be specified");
+        return;
+    }
+    let user_name = &args[1];
+
+    // connect to database
+    dotenv::dotenv().ok();
+    let db = database::get_database_pool();
+    let conn: &PgConnection = &db.get().unwrap();
+
+    // issue the reset token, to be redeemed at POST /auth/password_reset
+    match tool_helpers::create_password_reset(conn, user_name) {
+        Some((reset, token)) => {
+            println!("Password reset token for {:?}, expires at {}:", user_name, reset.expires_at);
+            println!("{}", token);
+        },
+        None => println!("No user found with name {:?}", user_name),
+    }
+}
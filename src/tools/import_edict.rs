@@ -6,13 +6,17 @@ use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader, ErrorKind};
 use std::path::Path;
-use diesel::PgConnection;
+use diesel::prelude::*;
 use dotenv;
 use regex::Regex;
 
 use langis::app::database;
-use langis::app::models::{NewWordEntry,NewWordEntryGroup};
+use langis::app::models::NewWordEntry;
+use langis::app::search::reading_search_key;
+use langis::app::tags::{find_tag, is_tag, TagCategory, TagSource};
 use langis::helpers::{edict_helpers,tool_helpers};
+use langis::helpers::bulk_writer::BulkWriter;
+use langis::helpers::tool_helpers::UpstreamGroups;
 
 lazy_static::lazy_static! {
     // parse language identifier from filename
@@ -21,26 +25,35 @@ lazy_static::lazy_static! {
     pub static ref ENTL_RGX: Regex = Regex::new(r"^EntL(?:\d+)X?$").unwrap();
     // matches {bracket} tags
     pub static ref BRACKET_TAGS_RGX: Regex = Regex::new(r"([^{]*)(\{.+\})(.*)").unwrap();
-    // list of edict grammar part-of-speech tags
-    pub static ref EDICT_POS: Vec<&'static str> = vec!["adj-f","adj-i","adj-ix","adj-na",
-        "adj-nari","adj-no","adj-pn","adj-t","adv","adv-to","aux","aux-adj","aux-v","conj",
-        "cop","ctr","exp","int","n","n-adv","n-pref","n-suf","n-t","num","pn","pref","prt",
-        "suf","v1","v1-s","v2a-s","v2b-k","v2d-s","v2g-k","v2g-s","v2h-k","v2h-s","v2k-k",
-        "v2k-s","v2m-s","v2n-s","v2r-k","v2r-s","v2s-s","v2t-k","v2t-s","v2w-s","v2y-k",
-        "v2y-s","v2z-s","v4b","v4g","v4h","v4k","v4m","v4r","v4s","v4t","v5aru","v5b","v5g",
-        "v5k","v5k-s","v5m","v5n","v5r","v5r-i","v5s","v5t","v5u","v5u-s","vi","vk","vn","vr",
-        "vs","vs-c","vs-i","vs-s","vt","vz"];
-    // list of edict orth tags
-    pub static ref EDICT_ORTH_TAGS: Vec<&'static str> = vec!["P","ik","iK","io","ateji","ok","oK","oik"];
-    // list of edict bracket tags
-    pub static ref EDICT_BRACKET_TAGS: Vec<&'static str> = vec!["anat","archit","astron","baseb","biol",
-        "bot","Buddh","bus","chem","Christn","comp","econ","engr","finc","food","geol","geom","law",
-        "ling","MA","mahj","math","med","mil","music","physics","Shinto","shogi","sports","sumo","zool"];
-    // special tags from cedict file
-    pub static ref CEDICT_TAGS: Vec<&'static str> = vec!["anatomy","archaic","behavior","brand","Buddhism","botany",
-        "Cantonese","character","chemistry","coll.","colloquial","computing","dialect","derog.","fig.","finance","geology",
-        "grammar","honorific","idiom","Internet slang","law","literary","linguistics","loanword","math.","meaning unclear",
-        "medicine","military","music","name","old","onom.","physics","polite","proverb","slang","sports","Tw"];
+}
+
+/// fixes specific lines in the edict2 and cedict files
+fn fix_line(line_raw: String) -> String {
+    if line_raw.starts_with("倍速 [ばいそく] /(adj-pn) (1) {comp} double-speed (drive, etc.}/") {
+        // bugged parenthesis
+        line_raw.replace("(drive, etc.}","(drive, etc.)")
+    } else if line_raw.starts_with("如是 [にょぜ] /(n) (1) {Buddh} (See 如是我聞) (\"like this\"; often the opening word of a sutra)") {
+        // my parser cant handle quotes with only paren-text
+        line_raw.replace("(\"like this\"; often","\"like this\" (often")
+    } else if line_raw.starts_with("唐棕櫚;唐棕梠 [とうじゅろ;トウジュロ] /(n) miniature Chusan palm (Trachycarpus wagnerianus)/(poss. Trachycarpus fortunei)") {
+        // combine these into single quote text
+        line_raw.replace("(Trachycarpus wagnerianus)/(poss. Trachycarpus fortunei)","(Trachycarpus wagnerianus) (poss. Trachycarpus fortunei)")
+    } else if line_raw.starts_with("大牌檔 大牌档 [da4 pai2 dang4] /food stall/open-air restaurant (originally Hong Kong usage, now usually written as 大排檔|大排档[da4 pai2 dang4]") {
+        // unbalanced parentheses
+        line_raw.replace("大排檔|大排档[da4 pai2 dang4]","大排檔|大排档[da4 pai2 dang4])")
+    } else if line_raw.starts_with("掖庭 掖庭 [ye4 ting2] /Lateral Courts in the imperial palace (housing concubines and administrative offices") {
+        // unbalanced parentheses
+        line_raw.replace("administrative offices","administrative offices)")
+    } else if line_raw.starts_with("歹勢 歹势 [dai3 shi4] /(Tw) excuse me/to be sorry/(Taiwanese, Tai-lo pr. [pháinn-sè]") {
+        // unbalanced parentheses
+        line_raw.replace("Tai-lo pr. [pháinn-sè]","Tai-lo pr. [pháinn-sè])")
+    } else if line_raw.starts_with("知人者智，自知者明 知人者智，自知者明 [zhi1 ren2 zhe3 zhi4 , zi4 zhi1 zhe3 ming2] /those who understand others are clever, but those who know themselves are truly wise (idiom, from Laozi's 道德經|道德经[Dao4 de2 jing1]") {
+        // unbalanced parentheses, also split the notes
+        line_raw.replace("(idiom, from Laozi's 道德經|道德经[Dao4 de2 jing1]","(idiom) (from Laozi's 道德經|道德经[Dao4 de2 jing1])")
+    } else if line_raw.starts_with("能願動詞 能愿动词 [neng2 yuan4 dong4 ci2] /modal verb (e.g. 肯[ken3], 能[neng2], 會|会[hui4], 要[yao4], 該|该[gai1], 得[dei3], 願意|愿意[yuan4 yi4], 可以[ke3 yi3], 可能[ke3 neng2], 敢[gan3], 應該|应该[ying1 gai1]") {
+        // unbalanced parentheses
+        line_raw.replace("應該|应该[ying1 gai1]/","應該|应该[ying1 gai1])")
+    } else { line_raw }
 }
 
 /// main
@@ -72,353 +85,371 @@ fn main() -> std::io::Result<()> {
     let file = File::open(filename)?;
     let file_reader = BufReader::new(file);
 
-    // find or create sources record
-    let source_name = Path::new(filename).file_stem().unwrap().to_str().unwrap();
-    let source = tool_helpers::find_or_create_source(&conn, source_name.to_string());
+    // read the entry lines, skipping lines that begin with # and the edict2 header line
+    let mut entry_lines = Vec::new();
+    for line in file_reader.lines() {
+        match line {
+            Ok(line_raw) => if !(line_raw.starts_with("#") || line_raw.starts_with("　？？？")) {
+                entry_lines.push(line_raw);
+            },
+            Err(er) => {
+                if er.kind() == ErrorKind::InvalidData {
+                    println!("Invalid data encountered, perhaps this edict file has not been converted to utf8?");
+                } else {
+                    println!("Unknown Error\n{:?}", er);
+                }
+                return Err(er);
+            },
+        }
+    }
 
-    // tracks number of entries encountered
-    let mut entry_count = 0;
+    // digests of the unmodified lines, to detect modified entries on updates
+    let upstream_hashes = entry_lines.iter().map(|line_raw| edict_helpers::line_hash(line_raw)).collect::<Vec<_>>();
+    // key the entries by their EntL id or headword, cedict lines of the same headword share a key
+    let upstream_keys = entry_lines.iter()
+        .map(|line_raw| edict_helpers::upstream_key(&edict_helpers::split_by_outer_slashes(&fix_line(line_raw.clone()))))
+        .collect::<Vec<_>>();
+
+    // write the whole import in one transaction, so a failed import leaves nothing behind
+    let entry_count = conn.transaction::<_, diesel::result::Error, _>(|| {
+        let mut writer = BulkWriter::new(conn);
+
+        // find or create sources record
+        let source_name = Path::new(filename).file_stem().unwrap().to_str().unwrap();
+        let source = tool_helpers::find_or_create_source(&conn, source_name.to_string());
+
+        // replace the entries of a previous import without upstream keys
+        let replaced_count = tool_helpers::delete_unkeyed_source_entries(writer.conn(), source.id);
+        if replaced_count > 0 {
+            println!("Replacing {} word entry groups of the previous import", replaced_count);
+        }
 
-    // begin
-    println!("Beginning import of edict file with orth language: {:?}, quote language: \"eng\"", lang_id);
+        // groups of the previous import by upstream key, entries left over are no longer upstream
+        let mut upstream_groups = UpstreamGroups::load(writer.conn(), source.id);
+        // keep the groups of unchanged lines first, so modified lines of the same headword can't take them
+        let unchanged_lines = upstream_keys.iter().zip(&upstream_hashes)
+            .map(|(upstream_key, upstream_hash)| upstream_groups.keep_unchanged(upstream_key, upstream_hash))
+            .collect::<Vec<_>>();
+
+        // tracks number of entries encountered
+        let mut entry_count = 0;
+
+        // begin
+        println!("Beginning import of edict file with orth language: {:?}, quote language: \"eng\"", lang_id);
+
+        // import the entries with their keys
+        let keyed_lines = entry_lines.into_iter().zip(upstream_hashes).zip(upstream_keys).zip(unchanged_lines);
+        for (((line_raw, upstream_hash), upstream_key), unchanged) in keyed_lines {
+            // incr
+            entry_count += 1;
+            if unchanged {
+                continue;
+            }
 
-    // Consumes a lazy iterator, reading the file line-by-line
-    for line in file_reader.lines() {
-        if let Ok(line_raw) = line {
-            if line_raw.starts_with("#") {
-                // skip lines that begin with #
-            } else if line_raw.starts_with("　？？？") {
-                // skip the edict2 header line
+            // fix specific lines in the edict2 and cedict files
+            let line_text = fix_line(line_raw);
+
+            // split the line by '/' to separate orth and quote parts
+            let line_parts = edict_helpers::split_by_outer_slashes(&line_text);
+
+            // insert word_entry_groups record, or replace the group of a modified entry
+            let group_id = match upstream_groups.upsert_keyed_group(&mut writer, upstream_key, upstream_hash)? {
+                Some(group_id) => group_id,
+                // unchanged since the previous import
+                None => continue,
+            };
+
+            // match orth part with optional reading group
+            let orth_caps = ORTH_RGX.captures(line_parts.first().unwrap()).unwrap();
+
+            let orth = orth_caps.get(1).map_or("", |m| m.as_str()).trim();
+            let readings = orth_caps.get(2).map_or("", |m| m.as_str()).trim();
+
+            // edict2 readings are split by ;, cedict readings are split by a space
+            let orth_splitter = if lang_id == "zho" { " " } else { ";" };
+            let orth_parts: Vec<&str> = orth.split(orth_splitter).collect();
+
+            // edict2 can have multiple readings split by ;, cedict does not have multiple readings
+            let reading_parts: Vec<&str> = if lang_id == "zho" {
+                vec![readings]
             } else {
-                // insert word_entry_groups record
-                let new_group = NewWordEntryGroup {
-                    source_id: source.id
-                };
-                let group_id = tool_helpers::insert_word_entry_group(&conn, new_group);
-
-                // fix specific lines in the edict2 and cedict files
-                let line_text = if line_raw.starts_with("倍速 [ばいそく] /(adj-pn) (1) {comp} double-speed (drive, etc.}/") {
-                    // bugged parenthesis
-                    line_raw.replace("(drive, etc.}","(drive, etc.)")
-                } else if line_raw.starts_with("如是 [にょぜ] /(n) (1) {Buddh} (See 如是我聞) (\"like this\"; often the opening word of a sutra)") {
-                    // my parser cant handle quotes with only paren-text
-                    line_raw.replace("(\"like this\"; often","\"like this\" (often")
-                } else if line_raw.starts_with("唐棕櫚;唐棕梠 [とうじゅろ;トウジュロ] /(n) miniature Chusan palm (Trachycarpus wagnerianus)/(poss. Trachycarpus fortunei)") {
-                    // combine these into single quote text
-                    line_raw.replace("(Trachycarpus wagnerianus)/(poss. Trachycarpus fortunei)","(Trachycarpus wagnerianus) (poss. Trachycarpus fortunei)")
-                } else if line_raw.starts_with("大牌檔 大牌档 [da4 pai2 dang4] /food stall/open-air restaurant (originally Hong Kong usage, now usually written as 大排檔|大排档[da4 pai2 dang4]") {
-                    // unbalanced parentheses
-                    line_raw.replace("大排檔|大排档[da4 pai2 dang4]","大排檔|大排档[da4 pai2 dang4])")
-                } else if line_raw.starts_with("掖庭 掖庭 [ye4 ting2] /Lateral Courts in the imperial palace (housing concubines and administrative offices") {
-                    // unbalanced parentheses
-                    line_raw.replace("administrative offices","administrative offices)")
-                } else if line_raw.starts_with("歹勢 歹势 [dai3 shi4] /(Tw) excuse me/to be sorry/(Taiwanese, Tai-lo pr. [pháinn-sè]") {
-                    // unbalanced parentheses
-                    line_raw.replace("Tai-lo pr. [pháinn-sè]","Tai-lo pr. [pháinn-sè])")
-                } else if line_raw.starts_with("知人者智，自知者明 知人者智，自知者明 [zhi1 ren2 zhe3 zhi4 , zi4 zhi1 zhe3 ming2] /those who understand others are clever, but those who know themselves are truly wise (idiom, from Laozi's 道德經|道德经[Dao4 de2 jing1]") {
-                    // unbalanced parentheses, also split the notes
-                    line_raw.replace("(idiom, from Laozi's 道德經|道德经[Dao4 de2 jing1]","(idiom) (from Laozi's 道德經|道德经[Dao4 de2 jing1])")
-                } else if line_raw.starts_with("能願動詞 能愿动词 [neng2 yuan4 dong4 ci2] /modal verb (e.g. 肯[ken3], 能[neng2], 會|会[hui4], 要[yao4], 該|该[gai1], 得[dei3], 願意|愿意[yuan4 yi4], 可以[ke3 yi3], 可能[ke3 neng2], 敢[gan3], 應該|应该[ying1 gai1]") {
-                    // unbalanced parentheses
-                    line_raw.replace("應該|应该[ying1 gai1]/","應該|应该[ying1 gai1])")
-                } else { line_raw };
-
-                // split the line by '/' to separate orth and quote parts
-                let line_parts = edict_helpers::split_by_outer_slashes(&line_text);
-
-                // match orth part with optional reading group
-                let orth_caps = ORTH_RGX.captures(line_parts.first().unwrap()).unwrap();
-
-                let orth = orth_caps.get(1).map_or("", |m| m.as_str()).trim();
-                let readings = orth_caps.get(2).map_or("", |m| m.as_str()).trim();
-
-                // edict2 readings are split by ;, cedict readings are split by a space
-                let orth_splitter = if lang_id == "zho" { " " } else { ";" };
-                let orth_parts: Vec<&str> = orth.split(orth_splitter).collect();
-
-                // edict2 can have multiple readings split by ;, cedict does not have multiple readings
-                let reading_parts: Vec<&str> = if lang_id == "zho" {
-                    vec![readings]
-                } else {
-                    readings.split(";").collect()
-                };
-                // collect readings with and without specifiers
-                let mut shared_readings: Vec<(&str,Option<String>)> = Vec::new();
-                let mut specified_readings: HashMap<&str,Vec<(&str,Option<String>)>> = HashMap::new();
-                for reading in reading_parts.clone() {
-                    // skip unknown readings marked with xx
-                    if !reading.contains("xx") {
-                        if reading.contains('(') {
-                            // specific reading
-                            let sp_parts: Vec<&str> = reading.split('(').collect();
+                readings.split(";").collect()
+            };
+            // collect readings with and without specifiers
+            let mut shared_readings: Vec<(&str,Option<String>)> = Vec::new();
+            let mut specified_readings: HashMap<&str,Vec<(&str,Option<String>)>> = HashMap::new();
+            for reading in reading_parts.clone() {
+                // skip unknown readings marked with xx
+                if !reading.contains("xx") {
+                    if reading.contains('(') {
+                        // specific reading
+                        let sp_parts: Vec<&str> = reading.split('(').collect();
+                        // filter out special-tag key parts
+                        let mut special_tags: Vec<String> = Vec::new();
+                        let filtered_sp_parts: Vec<&str> = sp_parts[1..].into_iter().map({|&sp_part|
+                            sp_part.trim_end_matches(')')
+                        }).filter({|&kp|
+                            if kp == "ok" || kp == "P" || kp == "ik" || kp == "gikun" {
+                                special_tags.push(kp.to_string());
+                                false
+                            } else { true }
+                        }).collect();
+                        for sp_key in filtered_sp_parts.into_iter() {
+                            // split specified parts by ,
+                            let sp_key_parts: Vec<&str> = sp_key.split(',').collect();
                             // filter out special-tag key parts
-                            let mut special_tags: Vec<String> = Vec::new();
-                            let filtered_sp_parts: Vec<&str> = sp_parts[1..].into_iter().map({|&sp_part|
-                                sp_part.trim_end_matches(')')
-                            }).filter({|&kp|
+                            let spp_key_parts: Vec<&str> = sp_key_parts.into_iter().filter({|&kp|
                                 if kp == "ok" || kp == "P" || kp == "ik" || kp == "gikun" {
-                                    special_tags.push(kp.to_string());
+                                    println!("WARNING! special tag key parts in spp list: {:?}", &sp_key);
                                     false
                                 } else { true }
                             }).collect();
-                            for sp_key in filtered_sp_parts.into_iter() {
-                                // split specified parts by ,
-                                let sp_key_parts: Vec<&str> = sp_key.split(',').collect();
-                                // filter out special-tag key parts
-                                let spp_key_parts: Vec<&str> = sp_key_parts.into_iter().filter({|&kp|
-                                    if kp == "ok" || kp == "P" || kp == "ik" || kp == "gikun" {
-                                        println!("WARNING! special tag key parts in spp list: {:?}", &sp_key);
-                                        false
-                                    } else { true }
-                                }).collect();
-                                if spp_key_parts.len() > 0 {
-                                    // if specified key parts
-                                    for spp_key in spp_key_parts {
-                                        let reading = sp_parts[0].clone().trim();
-                                        if reading != "" {
-                                            if !specified_readings.contains_key(spp_key) {
-                                                specified_readings.insert(spp_key, Vec::new());
-                                            }
-                                            specified_readings.get_mut(spp_key).unwrap().push((reading,Some(special_tags.join(","))));
+                            if spp_key_parts.len() > 0 {
+                                // if specified key parts
+                                for spp_key in spp_key_parts {
+                                    let reading = sp_parts[0].clone().trim();
+                                    if reading != "" {
+                                        if !specified_readings.contains_key(spp_key) {
+                                            specified_readings.insert(spp_key, Vec::new());
                                         }
+                                        specified_readings.get_mut(spp_key).unwrap().push((reading,Some(special_tags.join(","))));
                                     }
-                                } else {
-                                    // no specified key parts, just special-tag key parts, so collect as shared readings
-                                    let reading = sp_parts[0].clone().trim();
-                                    shared_readings.push((reading,Some(special_tags.join(","))));
                                 }
+                            } else {
+                                // no specified key parts, just special-tag key parts, so collect as shared readings
+                                let reading = sp_parts[0].clone().trim();
+                                shared_readings.push((reading,Some(special_tags.join(","))));
                             }
-                        } else {
-                            // shared reading
-                            let reading_trimmed = reading.trim();
-                            if reading_trimmed != "" {
-                                shared_readings.push((reading_trimmed,None));
-                            }
+                        }
+                    } else {
+                        // shared reading
+                        let reading_trimmed = reading.trim();
+                        if reading_trimmed != "" {
+                            shared_readings.push((reading_trimmed,None));
                         }
                     }
                 }
+            }
 
-                // split quote parts (definitions) by /
-                let quote_parts_raw = &line_parts[1..];
-                // filter out EntL ids and empty strings
-                let quote_parts: Vec<String> = quote_parts_raw.iter().filter({|qp|
-                    !(qp.is_empty() || ENTL_RGX.is_match(qp))
-                }).map(|qp| qp.clone()).collect();
-
-
-                // insert rows for each variation
-                for op in orth_parts {
-                    // for edict, parse out extra tags in the orth field
-                    let mut collected_orth_tags = Vec::<String>::new();
-                    let processed_orth = if lang_id == "jpn" {
-                        let orth_tag_parts: Vec<&str> = op.split('(').collect();
-                        for orth_tag_raw in orth_tag_parts[1..].into_iter() {
-                            let orth_tag = orth_tag_raw.trim_end_matches(')');
-                            if EDICT_ORTH_TAGS.contains(&orth_tag) {
-                                collected_orth_tags.push(orth_tag.to_string());
-                            } else {
-                                println!("WARNING! unknown orth tag: {:?}", orth_tag);
-                            }
+            // split quote parts (definitions) by /
+            let quote_parts_raw = &line_parts[1..];
+            // filter out EntL ids and empty strings
+            let quote_parts: Vec<String> = quote_parts_raw.iter().filter({|qp|
+                !(qp.is_empty() || ENTL_RGX.is_match(qp))
+            }).map(|qp| qp.clone()).collect();
+
+
+            // insert rows for each variation
+            for op in orth_parts {
+                // for edict, parse out extra tags in the orth field
+                let mut collected_orth_tags = Vec::<String>::new();
+                let processed_orth = if lang_id == "jpn" {
+                    let orth_tag_parts: Vec<&str> = op.split('(').collect();
+                    for orth_tag_raw in orth_tag_parts[1..].into_iter() {
+                        let orth_tag = orth_tag_raw.trim_end_matches(')');
+                        if is_tag(TagSource::Edict, TagCategory::Orth, orth_tag) {
+                            collected_orth_tags.push(orth_tag.to_string());
+                        } else {
+                            println!("WARNING! unknown orth tag: {:?}", orth_tag);
                         }
+                    }
 
-                        // return first part from split as processed_orth
-                        orth_tag_parts[0]
-                    } else {
-                        // no processing for cedict
-                        op
-                    };
+                    // return first part from split as processed_orth
+                    orth_tag_parts[0]
+                } else {
+                    // no processing for cedict
+                    op
+                };
 
-                    for (sense_idx, qp) in quote_parts.clone().into_iter().enumerate() {
-                        // extract known notes from edict2 quote strings
-                        // TODO extract See notes
-                        let mut collected_notes = Vec::<String>::new();
-                        let mut collected_tags = Vec::<String>::new();
-                        let (qp_rem, qp_notes) = edict_helpers::extract_outer_paren_groups(&qp);
-                        for qp_note in qp_notes {
-                            // trim single leading and trailing ( )
-                            let mut trimmed_note = qp_note.as_str();
-                            if trimmed_note.chars().next() == Some('(') {
-                                trimmed_note = &trimmed_note[1..];
-                            }
-                            if trimmed_note.chars().last() == Some(')') {
-                                trimmed_note = &trimmed_note[..trimmed_note.len()-1];
-                            }
-                            // check if note is known tag
-                            if (lang_id == "zho" && CEDICT_TAGS.contains(&trimmed_note)) ||
-                               EDICT_POS.contains(&trimmed_note)
-                            {
-                                // known tag
-                                collected_tags.push(trimmed_note.to_string());
-                            } else {
-                                // not known tag, store as note instead
-                                collected_notes.push(trimmed_note.to_string());
-                            }
+                for (sense_idx, qp) in quote_parts.clone().into_iter().enumerate() {
+                    // extract known notes from edict2 quote strings
+                    // TODO extract See notes
+                    let mut collected_notes = Vec::<String>::new();
+                    let mut collected_tags = Vec::<String>::new();
+                    let (qp_rem, qp_notes) = edict_helpers::extract_outer_paren_groups(&qp);
+                    for qp_note in qp_notes {
+                        // trim single leading and trailing ( )
+                        let mut trimmed_note = qp_note.as_str();
+                        if trimmed_note.chars().next() == Some('(') {
+                            trimmed_note = &trimmed_note[1..];
                         }
+                        if trimmed_note.chars().last() == Some(')') {
+                            trimmed_note = &trimmed_note[..trimmed_note.len()-1];
+                        }
+                        // check if note is known tag
+                        if (lang_id == "zho" && find_tag(TagSource::Cedict, trimmed_note).is_some()) ||
+                           is_tag(TagSource::Edict, TagCategory::Pos, trimmed_note)
+                        {
+                            // known tag
+                            collected_tags.push(trimmed_note.to_string());
+                        } else {
+                            // not known tag, store as note instead
+                            collected_notes.push(trimmed_note.to_string());
+                        }
+                    }
 
-                        // remainder str for { } extraction
-                        let mut rqp = qp_rem.clone();
+                    // remainder str for { } extraction
+                    let mut rqp = qp_rem.clone();
 
-                        loop {
-                            // ignore cedict line that describes curly brackets
-                            if rqp.starts_with("curly brackets { }") {
-                                break;
-                            }
+                    loop {
+                        // ignore cedict line that describes curly brackets
+                        if rqp.starts_with("curly brackets { }") {
+                            break;
+                        }
 
-                            // parse out known {bracket} tags
-                            let rqpc = rqp.clone();
-                            let tag_match_opt = BRACKET_TAGS_RGX.captures(rqpc.as_str());
-                            if let Some(tag_caps) = tag_match_opt {
-                                rqp = (tag_caps.get(1).map_or("", |m| m.as_str()).trim().to_string() +
-                                    " " + tag_caps.get(3).map_or("", |m| m.as_str()).trim()).trim().to_string();
-                                if let Some(matched_tag) = tag_caps.get(2) {
-                                    // sometimes bracket tags have multiple tags separated by ;
-                                    let split_tags: Vec<&str> = matched_tag.as_str().split(';').collect();
-                                    for split_tag in split_tags {
-                                        let trimmed_tag = split_tag.trim_start_matches('{').trim_end_matches('}');
-                                        if EDICT_BRACKET_TAGS.contains(&trimmed_tag) {
-                                            collected_tags.push(trimmed_tag.to_string());
-                                        } else {
-                                            println!("WARNING! unknown bracket tag: {:?}", trimmed_tag);
-                                            println!("  {:?}", rqpc);
-                                            println!();
-                                        }
+                        // parse out known {bracket} tags
+                        let rqpc = rqp.clone();
+                        let tag_match_opt = BRACKET_TAGS_RGX.captures(rqpc.as_str());
+                        if let Some(tag_caps) = tag_match_opt {
+                            rqp = (tag_caps.get(1).map_or("", |m| m.as_str()).trim().to_string() +
+                                " " + tag_caps.get(3).map_or("", |m| m.as_str()).trim()).trim().to_string();
+                            if let Some(matched_tag) = tag_caps.get(2) {
+                                // sometimes bracket tags have multiple tags separated by ;
+                                let split_tags: Vec<&str> = matched_tag.as_str().split(';').collect();
+                                for split_tag in split_tags {
+                                    let trimmed_tag = split_tag.trim_start_matches('{').trim_end_matches('}');
+                                    if is_tag(TagSource::Edict, TagCategory::Domain, trimmed_tag) {
+                                        collected_tags.push(trimmed_tag.to_string());
+                                    } else {
+                                        println!("WARNING! unknown bracket tag: {:?}", trimmed_tag);
+                                        println!("  {:?}", rqpc);
+                                        println!();
                                     }
                                 }
-                            } else {
-                                break;
                             }
+                        } else {
+                            break;
                         }
+                    }
 
-                        // trim whitespace from remainder text
-                        rqp = rqp.trim().to_string();
-
-                        // if we ended up with empty quote text
-                        if rqp == "" {
-                            if collected_notes.len() == 1 && collected_notes[0] == "P" {
-                                // ignore /(P)/ because it seems to be duplicated in the orth
-                                // and i dont know how to handle this case
-                                continue;
-                            } else if collected_notes.len() == 1 && collected_notes[0] == "sometimes called \"negative electricity\"" {
-                                // skip this quote/note because its hard to handle and hopefully not useful
-                                continue;
-                            } else if collected_notes.len() == 1 && collected_notes[0] == "sometimes called \"positive electricity\"" {
-                                // also skip this one (there are actually two)
-                                continue;
-                            } else if collected_notes.len() == 1 && collected_notes[0] == "powerful Turkic confederation from medieval Inner Asia" {
-                                // easiest to just turn this one into quote text
-                                rqp = "powerful Turkic confederation from medieval Inner Asia".to_string();
-                            } else {
-                                // cedict has several entries with note text but no quote text
-                                // edict should have none, so print warnings
-                                if lang_id == "jpn" {
-                                    println!("WARNING! unexpected empty quote: {:?}, notes: {:?}, tags: {:?}", rqp, collected_notes, collected_tags);
-                                    println!("  {:?}", line_text);
-                                    println!();
-                                }
+                    // trim whitespace from remainder text
+                    rqp = rqp.trim().to_string();
+
+                    // if we ended up with empty quote text
+                    if rqp == "" {
+                        if collected_notes.len() == 1 && collected_notes[0] == "P" {
+                            // ignore /(P)/ because it seems to be duplicated in the orth
+                            // and i dont know how to handle this case
+                            continue;
+                        } else if collected_notes.len() == 1 && collected_notes[0] == "sometimes called \"negative electricity\"" {
+                            // skip this quote/note because its hard to handle and hopefully not useful
+                            continue;
+                        } else if collected_notes.len() == 1 && collected_notes[0] == "sometimes called \"positive electricity\"" {
+                            // also skip this one (there are actually two)
+                            continue;
+                        } else if collected_notes.len() == 1 && collected_notes[0] == "powerful Turkic confederation from medieval Inner Asia" {
+                            // easiest to just turn this one into quote text
+                            rqp = "powerful Turkic confederation from medieval Inner Asia".to_string();
+                        } else {
+                            // cedict has several entries with note text but no quote text
+                            // edict should have none, so print warnings
+                            if lang_id == "jpn" {
+                                println!("WARNING! unexpected empty quote: {:?}, notes: {:?}, tags: {:?}", rqp, collected_notes, collected_tags);
+                                println!("  {:?}", line_text);
+                                println!();
                             }
                         }
+                    }
 
-                        // show warning if nothing useful was parsed
-                        if rqp == "" && collected_notes.len() == 0 && collected_tags.len() == 0 {
-                            println!("WARNING! unexpected empty; quote: {:?}, notes: {:?}, tags: {:?}", rqp, collected_notes, collected_tags);
-                            println!("  {:?}", line_text);
-                            println!();
-                        }
+                    // show warning if nothing useful was parsed
+                    if rqp == "" && collected_notes.len() == 0 && collected_tags.len() == 0 {
+                        println!("WARNING! unexpected empty; quote: {:?}, notes: {:?}, tags: {:?}", rqp, collected_notes, collected_tags);
+                        println!("  {:?}", line_text);
+                        println!();
+                    }
 
 
-                        // insert word_entries record
-                        let new_entry = NewWordEntry {
-                            orth: processed_orth.to_string(),
-                            orth_lang: lang_id.to_string(),
-                            quote: rqp.to_string(),
-                            quote_lang: "eng".to_string(),
-                            sense: sense_idx as i32,
-                            group_id: group_id
-                        };
-                        let word_entry_id = tool_helpers::insert_word_entry(&conn, new_entry);
-
-                        // process notes for additional tags
-                        let processed_notes = collected_notes.into_iter().map({|note|
-                            // only for edict, not cedict
-                            if lang_id == "jpn" {
-                                // comp tags are just special notes
-                                if note == "{comp}" {
-                                    "comp".to_string()
-                                // idk what unc stands for, but it refers to special grammar markings
-                                } else if note == "unc" {
-                                    "unc".to_string()
-                                } else {
-                                    // split by ,
-                                    let split_note: Vec<&str> = note.split(',').collect();
-                                    let split_note_len = (&split_note).len();
-                                    // check if all parts are known edict pos tags
-                                    let pos_notes: Vec<&str> = split_note.into_iter().filter({|n|
-                                        EDICT_POS.contains(n)
-                                    }).collect();
-                                    if pos_notes.len() == 0 {
-                                        //  no pos tags
-                                        note.to_string()
-                                    } else if pos_notes.len() == split_note_len {
-                                        // all pos tags, store each as seperate tag
-                                        for pos_note in pos_notes {
-                                            collected_tags.push(pos_note.to_string());
-                                        }
-                                        // return empty note
-                                        "".to_string()
-                                    } else {
-                                        // note with mixed pos and non-pos tags is an error case
-                                        println!("WARNING! note with unknown pos tags: {:?}", note);
-                                        "".to_string()
+                    // insert word_entries record
+                    let new_entry = NewWordEntry {
+                        orth: processed_orth.to_string(),
+                        orth_lang: lang_id.to_string(),
+                        quote: rqp.to_string(),
+                        quote_lang: "eng".to_string(),
+                        sense: sense_idx as i32,
+                        group_id: group_id
+                    };
+                    let word_entry_id = writer.add_entry(new_entry)?;
+
+                    // process notes for additional tags
+                    let processed_notes = collected_notes.into_iter().map({|note|
+                        // only for edict, not cedict
+                        if lang_id == "jpn" {
+                            // comp tags are just special notes
+                            if note == "{comp}" {
+                                "comp".to_string()
+                            // idk what unc stands for, but it refers to special grammar markings
+                            } else if note == "unc" {
+                                "unc".to_string()
+                            } else {
+                                // split by ,
+                                let split_note: Vec<&str> = note.split(',').collect();
+                                let split_note_len = (&split_note).len();
+                                // check if all parts are known edict pos tags
+                                let pos_notes: Vec<&str> = split_note.into_iter().filter({|n|
+                                    is_tag(TagSource::Edict, TagCategory::Pos, n)
+                                }).collect();
+                                if pos_notes.len() == 0 {
+                                    //  no pos tags
+                                    note.to_string()
+                                } else if pos_notes.len() == split_note_len {
+                                    // all pos tags, store each as seperate tag
+                                    for pos_note in pos_notes {
+                                        collected_tags.push(pos_note.to_string());
                                     }
+                                    // return empty note
+                                    "".to_string()
+                                } else {
+                                    // note with mixed pos and non-pos tags is an error case
+                                    println!("WARNING! note with unknown pos tags: {:?}", note);
+                                    "".to_string()
                                 }
-                            } else {
-                                // no processing for cedict
-                                note.to_string()
                             }
-                        }).filter({|pn| !pn.is_empty()});
-
-                        // insert notes
-                        for note in processed_notes {
-                            tool_helpers::insert_word_entry_note(&conn, word_entry_id, note);
+                        } else {
+                            // no processing for cedict
+                            note.to_string()
                         }
+                    }).filter({|pn| !pn.is_empty()});
 
-                        // insert orth tags
-                        for orth_tag in collected_orth_tags.clone() {
-                            tool_helpers::insert_word_entry_tag(&conn, word_entry_id, orth_tag);
-                        }
+                    // insert notes
+                    for note in processed_notes {
+                        writer.add_note(word_entry_id, note);
+                    }
 
-                        // insert other tags
-                        for tag in collected_tags.clone() {
-                            tool_helpers::insert_word_entry_tag(&conn, word_entry_id, tag);
-                        }
+                    // insert orth tags
+                    for orth_tag in collected_orth_tags.clone() {
+                        writer.add_tag(word_entry_id, orth_tag);
+                    }
 
-                        // insert readings
-                        for reading in shared_readings.clone() {
-                            tool_helpers::insert_word_entry_reading(&conn, word_entry_id, reading.0.to_string(), reading.1.clone());
-                        }
-                        if specified_readings.contains_key(processed_orth) {
-                            for reading in specified_readings.get(processed_orth).unwrap() {
-                                tool_helpers::insert_word_entry_reading(&conn, word_entry_id, reading.0.to_string(), reading.1.clone());
-                            }
+                    // insert other tags
+                    for tag in collected_tags.clone() {
+                        writer.add_tag(word_entry_id, tag);
+                    }
+
+                    // insert readings, with normalized search keys
+                    for reading in shared_readings.clone() {
+                        writer.add_reading(word_entry_id, reading.0.to_string(), reading.1.clone(),
+                            reading_search_key(&lang_id, reading.0));
+                    }
+                    if specified_readings.contains_key(processed_orth) {
+                        for reading in specified_readings.get(processed_orth).unwrap() {
+                            writer.add_reading(word_entry_id, reading.0.to_string(), reading.1.clone(),
+                                reading_search_key(&lang_id, reading.0));
                         }
                     }
                 }
-
-                // incr
-                entry_count += 1;
             }
-        } else if let Err(er) = line {
-            if er.kind() == ErrorKind::InvalidData {
-                println!("Invalid data encountered, perhaps this edict file has not been converted to utf8?");
-            } else {
-                println!("Unknown Error\n{:?}", er);
-            }
-            return Err(er);
         }
-    }
 
-    // update sources table with last_updated_at
-    tool_helpers::update_source(&conn, source.id);
+        // delete entries removed upstream
+        upstream_groups.finish(&mut writer);
+
+        // update sources table with last_updated_at
+        tool_helpers::update_source(&conn, source.id);
+
+        // write remaining rows
+        writer.finish()?;
+        Ok(entry_count)
+    }).expect("Error importing entries");
 
     // done
     println!("Finished, processed {:?} entries", entry_count);
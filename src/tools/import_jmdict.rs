@@ -0,0 +1,131 @@
+extern crate diesel;
+
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use diesel::prelude::*;
+
+use langis::app::database;
+use langis::app::models::NewWordEntry;
+use langis::app::search::reading_search_key;
+use langis::helpers::{edict_helpers,tool_helpers};
+use langis::helpers::bulk_writer::BulkWriter;
+use langis::helpers::jmdict_helpers::JmdictReader;
+use langis::helpers::tool_helpers::UpstreamGroups;
+
+/// main
+fn main() -> std::io::Result<()> {
+    // get input file path and optional gloss languages from command line arguments
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        println!("Usage: `import-jmdict path/to/JMdict_e.xml [gloss-languages]`");
+        println!("  file must be specified, gloss languages are comma separated, e.g. eng,ger, default all");
+        return Ok(());
+    }
+    let filename = &args[1];
+    let gloss_langs: Option<Vec<&str>> = args.get(2).map(|langs| langs.split(',').map(|lang| lang.trim()).collect());
+    // part of each entry digest, so changing the gloss languages rewrites all entries
+    let gloss_langs_key = gloss_langs.as_ref().map_or(String::new(), |langs| langs.join(","));
+
+    // connect to database
+    dotenv::dotenv().ok();
+    let db = database::get_database_pool();
+    let conn: &PgConnection = &db.get().unwrap();
+
+    // initialize file and xml reader
+    let file = File::open(filename)?;
+    let mut reader = JmdictReader::new(BufReader::new(file));
+
+    // write the whole import in one transaction, so a failed import leaves nothing behind
+    let entry_count = conn.transaction::<_, diesel::result::Error, _>(|| {
+        let mut writer = BulkWriter::new(conn);
+
+        // find or create sources record
+        let source_name = Path::new(filename).file_stem().unwrap().to_str().unwrap();
+        let source = tool_helpers::find_or_create_source(conn, source_name.to_string());
+
+        // replace the entries of a previous import without upstream keys
+        let replaced_count = tool_helpers::delete_unkeyed_source_entries(writer.conn(), source.id);
+        if replaced_count > 0 {
+            println!("Replacing {} word entry groups of the previous import", replaced_count);
+        }
+
+        // groups of the previous import by upstream key, entries left over are no longer upstream
+        let mut upstream_groups = UpstreamGroups::load(writer.conn(), source.id);
+
+        // tracks number of entries encountered
+        let mut entry_count = 0;
+
+        // begin
+        println!("Beginning import of JMdict file with gloss languages: {:?}", gloss_langs);
+
+        loop {
+            let entry = match reader.read_entry() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break, // exits the loop when reaching end of file
+                Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
+            };
+            entry_count += 1;
+
+            // skip glosses in other languages, and entries without glosses left, any previous group is deleted
+            let jmdict_entries = entry.word_entries().into_iter()
+                .filter(|jmdict_entry| match gloss_langs {
+                    Some(ref gloss_langs) => gloss_langs.contains(&jmdict_entry.quote_lang.as_str()),
+                    None => true,
+                })
+                .collect::<Vec<_>>();
+            if jmdict_entries.is_empty() {
+                continue;
+            }
+
+            // insert word_entry_groups record, or replace the group of a modified entry
+            // the digest covers the unparsed entry and the gloss languages
+            let upstream_hash = edict_helpers::line_hash(&format!("{}\n{}", gloss_langs_key, entry.raw_xml));
+            let group_id = match upstream_groups.upsert_keyed_group(&mut writer, entry.upstream_key(), upstream_hash)? {
+                Some(group_id) => group_id,
+                // unchanged since the previous import
+                None => continue,
+            };
+
+            for jmdict_entry in jmdict_entries {
+                // insert word_entries record
+                let new_entry = NewWordEntry {
+                    orth: jmdict_entry.orth,
+                    orth_lang: "jpn".to_string(),
+                    quote: jmdict_entry.quote,
+                    quote_lang: jmdict_entry.quote_lang,
+                    sense: jmdict_entry.sense,
+                    group_id,
+                };
+                let word_entry_id = writer.add_entry(new_entry)?;
+
+                // insert notes, tags and readings, with normalized search keys
+                for note in jmdict_entry.notes {
+                    writer.add_note(word_entry_id, note);
+                }
+                for tag in jmdict_entry.tags {
+                    writer.add_tag(word_entry_id, tag);
+                }
+                for (reading, reading_tag) in jmdict_entry.readings {
+                    let search_key = reading_search_key("jpn", &reading);
+                    writer.add_reading(word_entry_id, reading, reading_tag, search_key);
+                }
+            }
+        }
+
+        // delete entries removed upstream
+        upstream_groups.finish(&mut writer);
+
+        // update sources table with last_updated_at
+        tool_helpers::update_source(conn, source.id);
+
+        // write remaining rows
+        writer.finish()?;
+        Ok(entry_count)
+    }).expect("Error importing entries");
+
+    // done
+    println!("Finished, processed {:?} entries", entry_count);
+    Ok(())
+}
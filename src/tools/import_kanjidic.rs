@@ -0,0 +1,111 @@
+extern crate diesel;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use diesel::prelude::*;
+
+use langis::app::database;
+use langis::app::models::{NewKanjiMeaning,NewKanjiReading};
+use langis::helpers::bulk_writer::MAX_ROWS_PER_INSERT;
+use langis::helpers::kanjidic_helpers::{Character,KanjidicReader};
+use langis::schema::{kanji,kanji_meanings,kanji_readings};
+
+/// characters written per batch
+const BATCH_SIZE: usize = 1000;
+
+/// inserts a batch of characters with their readings and meanings
+fn write_batch(conn: &PgConnection, characters: &[Character]) -> QueryResult<()> {
+    // insert kanji records, then join readings and meanings by literal
+    let new_kanji = characters.iter().map(|character| character.kanji.clone()).collect::<Vec<_>>();
+    let kanji_ids = diesel::insert_into(kanji::table)
+        .values(&new_kanji)
+        .returning((kanji::literal, kanji::id))
+        .get_results::<(String, i32)>(conn)?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let mut new_readings = Vec::new();
+    let mut new_meanings = Vec::new();
+    for character in characters {
+        let kanji_id = kanji_ids[&character.kanji.literal];
+        for (reading_type, reading) in &character.readings {
+            new_readings.push(NewKanjiReading { kanji_id, reading: reading.clone(), reading_type: reading_type.clone() });
+        }
+        for (meaning_lang, meaning) in &character.meanings {
+            new_meanings.push(NewKanjiMeaning { kanji_id, meaning: meaning.clone(), meaning_lang: meaning_lang.clone() });
+        }
+    }
+    for chunk in new_readings.chunks(MAX_ROWS_PER_INSERT) {
+        diesel::insert_into(kanji_readings::table)
+            .values(chunk)
+            .execute(conn)?;
+    }
+    for chunk in new_meanings.chunks(MAX_ROWS_PER_INSERT) {
+        diesel::insert_into(kanji_meanings::table)
+            .values(chunk)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// main
+fn main() -> std::io::Result<()> {
+    // get input file path from command line argument
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        println!("Usage: `import-kanjidic path/to/kanjidic2.xml`");
+        println!("  file must be specified");
+        return Ok(());
+    }
+    let filename = &args[1];
+
+    // connect to database
+    dotenv::dotenv().ok();
+    let db = database::get_database_pool();
+    let conn: &PgConnection = &db.get().unwrap();
+
+    // initialize file and xml reader
+    let file = File::open(filename)?;
+    let mut reader = KanjidicReader::new(BufReader::new(file));
+
+    // tracks number of characters encountered
+    let mut character_count = 0;
+
+    // begin
+    println!("Beginning import of KANJIDIC2 file");
+
+    // replace the previously imported characters in one transaction
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        // readings and meanings are deleted with their characters
+        let replaced_count = diesel::delete(kanji::table).execute(conn)?;
+        if replaced_count > 0 {
+            println!("Replacing {} previously imported characters", replaced_count);
+        }
+
+        let mut characters = Vec::with_capacity(BATCH_SIZE);
+        loop {
+            match reader.read_character() {
+                Ok(Some(character)) => characters.push(character),
+                Ok(None) => break, // exits the loop when reaching end of file
+                Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
+            }
+            character_count += 1;
+
+            if characters.len() >= BATCH_SIZE {
+                write_batch(conn, &characters)?;
+                characters.clear();
+                println!("  wrote {} characters", character_count);
+            }
+        }
+        if !characters.is_empty() {
+            write_batch(conn, &characters)?;
+        }
+        Ok(())
+    }).expect("Error importing characters");
+
+    // done
+    println!("Finished, processed {:?} characters", character_count);
+    Ok(())
+}
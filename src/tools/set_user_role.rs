@@ -0,0 +1,37 @@
+extern crate diesel;
+
+use std::env;
+use diesel::PgConnection;
+
+use langis::app::models::Role;
+use langis::app::database;
+use langis::helpers::tool_helpers;
+
+/// main
+fn main() {
+    // get user name and role from command line arguments
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        println!("Usage: `set-user-role [user name] [reader|editor|admin]`");
+        println!("  user name and role must be specified");
+        return;
+    }
+    let user_name = &args[1];
+    let role = match args[2].parse::<Role>() {
+        Ok(role) => role,
+        Err(err) => {
+            println!("{}, expected one of reader, editor or admin", err);
+            return;
+        }
+    };
+
+    // connect to database
+    dotenv::dotenv().ok();
+    let db = database::get_database_pool();
+    let conn: &PgConnection = &db.get().unwrap();
+
+    match tool_helpers::set_user_role(conn, user_name, role) {
+        Some(user) => println!("Set role of {:?} to {}", user.name, user.role),
+        None => println!("No user found with name {:?}", user_name),
+    }
+}
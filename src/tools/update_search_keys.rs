@@ -0,0 +1,26 @@
+extern crate diesel;
+
+use diesel::prelude::*;
+
+use langis::app::database;
+use langis::helpers::tool_helpers;
+
+/// orth languages with reading search keys
+const SEARCH_KEY_LANGS: &[&str] = &["zho", "jpn"];
+
+/// main
+fn main() {
+    // connect to database
+    dotenv::dotenv().ok();
+    let db = database::get_database_pool();
+    let conn: &PgConnection = &db.get().unwrap();
+
+    // recompute all keys in one transaction, so searches never see a partial update
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        for lang in SEARCH_KEY_LANGS {
+            let updated_count = tool_helpers::update_reading_search_keys(conn, lang);
+            println!("Updated {} {} reading search keys", updated_count, lang);
+        }
+        Ok(())
+    }).expect("Error updating search keys");
+}